@@ -49,30 +49,6 @@ pub struct Term {
     pub period: u8,
 }
 
-#[derive(Debug, PartialEq, Eq, Deserialize)]
-#[serde(untagged)]
-enum DependenciesEnum {
-    Single(Code),
-    Multiple(Vec<Code>),
-}
-
-#[derive(Debug, PartialEq, Eq, Deserialize)]
-#[serde(rename_all = "camelCase", from = "DependenciesEnum")]
-struct InnerDependencies {
-    dependencies: Vec<Code>,
-}
-
-impl From<DependenciesEnum> for InnerDependencies {
-    fn from(dependencies: DependenciesEnum) -> Self {
-        InnerDependencies {
-            dependencies: match dependencies {
-                DependenciesEnum::Single(dependency) => vec![dependency],
-                DependenciesEnum::Multiple(dependencies) => dependencies,
-            },
-        }
-    }
-}
-
 #[serde_as]
 #[derive(Debug, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "camelCase")]
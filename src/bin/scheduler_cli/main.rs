@@ -2,12 +2,20 @@ use clap::Parser;
 use itertools::Itertools;
 use scheduler::loaders::json_loader::load;
 use scheduler::models::Code;
-use scheduler::option_generator::filters::{ChoiceIterator, CreditCount, SubjectCount};
-use scheduler::option_generator::OptionGenerator;
+use scheduler::models::SubjectCommision;
+use scheduler::option_generator::filters::{
+    ChoiceFilter, ChoiceIterator, CreditCount, FilterStatistics, FreeSeats, SubjectCount,
+};
+use scheduler::option_generator::{
+    parse_filter, schedule_distance, schedule_key, Constraints, Equivalent, OptionGenerator,
+    OptionIterator,
+};
 use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fs::read_to_string;
+use std::iter;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 mod subject_iter;
 use subject_iter::SubjectIterable;
 
@@ -20,6 +28,30 @@ struct Args {
     mandatory: PathBuf,
     #[clap(short, long, value_parser)]
     blacklisted: Option<PathBuf>,
+    #[clap(short, long, value_parser = parse_commission)]
+    pin: Vec<(Code, String)>,
+    #[clap(short, long, value_parser = parse_commission)]
+    exclude: Vec<(Code, String)>,
+    #[clap(long)]
+    merge_cross_listed: bool,
+    #[clap(long)]
+    collapse_equivalent: bool,
+    #[clap(long)]
+    diverse: Option<usize>,
+    #[clap(long)]
+    min_free_seats: Option<u64>,
+    #[clap(long)]
+    filter_stats: bool,
+    /// Extra constraints, e.g. "free(friday) and start >= 09:00".
+    #[clap(long)]
+    filter: Option<String>,
+}
+
+fn parse_commission(s: &str) -> Result<(Code, String), String> {
+    let (code, name) = s
+        .split_once('=')
+        .ok_or("Commission must be given as CODE=NAME")?;
+    Ok((code.parse().map_err(|e| format!("{e}"))?, name.to_owned()))
 }
 
 fn load_codes(path: &Path) -> HashSet<Code> {
@@ -62,39 +94,133 @@ fn main() {
         .cloned()
         .whitelist_codes(&codes)
         .blacklist_codes(&mandatory)
-        .map(|sub| (sub.borrow().code, sub.borrow().commissions.clone()))
+        .map(|sub| (sub.borrow().code, sub.borrow().bundles()))
         .collect_vec();
 
     let mandatory_subjects = subjects
         .iter()
         .cloned()
         .whitelist_codes(&mandatory)
-        .map(|sub| (sub.borrow().code, sub.borrow().commissions.clone()))
+        .map(|sub| (sub.borrow().code, sub.borrow().bundles()))
         .collect_vec();
 
     let mut generator = OptionGenerator::default();
     generator
         .set_mandatory(mandatory_subjects)
         .set_optional(optional_subjects);
+    if args.merge_cross_listed {
+        generator.set_alternatives(
+            subjects
+                .iter()
+                .map(|sub| sub.borrow())
+                .filter(|sub| !sub.cross_listed.is_empty())
+                .map(|sub| HashSet::from_iter(iter::once(sub.code).chain(sub.cross_listed.clone())))
+                .unique_by(|codes| {
+                    codes
+                        .iter()
+                        .sorted_by_key(|c| (c.high, c.low))
+                        .cloned()
+                        .collect_vec()
+                })
+                .collect(),
+        );
+    }
+    for (code, name) in &args.pin {
+        if let Err(e) = generator.pin_commission(code, name) {
+            eprintln!("{e}");
+            return;
+        }
+    }
+    for (code, name) in &args.exclude {
+        if let Err(e) = generator.exclude_commission(code, name) {
+            eprintln!("{e}");
+            return;
+        }
+    }
+    if let Some(seats) = args.min_free_seats {
+        generator.retain_items(|com| FreeSeats(seats).allows(com));
+    }
+    if let Some(infeasibility) = generator.explain_infeasible() {
+        eprintln!("{infeasibility}");
+        return;
+    }
+    let constraints = Constraints {
+        credits: 20..=30,
+        subjects: 4..=5,
+    };
+    let extra_filter = match args.filter.as_deref().map(parse_filter).transpose() {
+        Ok(filter) => filter.map(Rc::new),
+        Err(e) => {
+            eprintln!("{}", args.filter.unwrap());
+            eprintln!("{}^ {e}", " ".repeat(e.position));
+            return;
+        }
+    };
+    let statistics = FilterStatistics::new();
     let options = generator
+        .clone()
         .generate()
-        .filter_choices(SubjectCount::new(4..=5))
-        .filter_choices(CreditCount::new(20..=30));
+        .filter_choices(
+            statistics.track("subjects", SubjectCount::new(constraints.subjects.clone())),
+        )
+        .filter_choices(statistics.track("credits", CreditCount::new(constraints.credits.clone())));
+    let options: Box<dyn Iterator<Item = Vec<Option<SubjectCommision>>>> = match &extra_filter {
+        Some(filter) => {
+            let filter = filter.clone();
+            Box::new(options.filter_choices(
+                statistics.track("filter", move |option: &Vec<_>| filter.filter(option)),
+            ))
+        }
+        None => Box::new(options),
+    };
+    let options: Box<dyn Iterator<Item = Vec<Option<SubjectCommision>>>> = match args.diverse {
+        Some(count) => Box::new(options.most_diverse(count, schedule_distance).into_iter()),
+        None => Box::new(options),
+    };
 
-    for option in options {
-        let filtered = option.into_iter().flatten().collect_vec();
+    let groups: Box<dyn Iterator<Item = Equivalent<SubjectCommision>>> = if args.collapse_equivalent
+    {
+        Box::new(options.collapse_equivalent(schedule_key).into_iter())
+    } else {
+        Box::new(options.map(|option| Equivalent {
+            options: vec![option],
+        }))
+    };
 
-        println!(
-            "{}",
-            filtered
-                .iter()
-                .enumerate()
-                .map(|(_i, com)| com.to_string())
-                .join(", ") //.join(&" \u{2588} ".green().to_string())
-        );
+    let format_option = |option: &Vec<Option<SubjectCommision>>| {
+        option
+            .iter()
+            .flatten()
+            .map(|com| com.to_string())
+            .join(", ") //.join(&" \u{2588} ".green().to_string())
+    };
+    let mut found = false;
+    for group in groups {
+        found = true;
+        println!("{}", format_option(group.representative()));
+        for option in &group.options[1..] {
+            println!("    or {}", format_option(option));
+        }
         //dbg!(combined);
     }
 
+    if args.filter_stats {
+        eprint!("{statistics}");
+    }
+
+    if !found {
+        // The --filter expression is kept as is, only the built-in constraints are relaxed.
+        let suggestions =
+            generator.suggest_relaxations_with(&constraints, 1000, |option: &Vec<_>| {
+                extra_filter
+                    .as_ref()
+                    .is_none_or(|filter| filter.filter(option))
+            });
+        for suggestion in suggestions {
+            eprintln!("{} ({} options)", suggestion.relaxation, suggestion.options);
+        }
+    }
+
     //dbg!(Week::combine(&cloud_a.schedule, &eco_km.schedule));
 
     //println!("{:?}", combined);
@@ -36,21 +36,14 @@ impl<'a, I: Iterator<Item = Arc<RefCell<Subject>>>> Iterator for Blacklist<'a, I
 }
 
 pub trait SubjectIterable: Iterator<Item = Arc<RefCell<Subject>>> {
-    fn get_by_code(&mut self, code: Code) -> Option<Arc<RefCell<Subject>>>
-    where
-        Self: Sized,
-    {
-        self.find(|sub| sub.borrow().code == code)
-    }
-
-    fn whitelist_codes(self, codes: &HashSet<Code>) -> Whitelist<Self>
+    fn whitelist_codes(self, codes: &HashSet<Code>) -> Whitelist<'_, Self>
     where
         Self: Sized,
     {
         Whitelist::new(self, codes)
     }
 
-    fn blacklist_codes(self, codes: &HashSet<Code>) -> Blacklist<Self>
+    fn blacklist_codes(self, codes: &HashSet<Code>) -> Blacklist<'_, Self>
     where
         Self: Sized,
     {
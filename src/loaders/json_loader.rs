@@ -2,20 +2,105 @@ use crate::models::*;
 use enum_map::enum_map;
 use itertools::Itertools;
 use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::error::Error;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 use std::sync::Arc;
 
+type ClassSignature = Vec<(u8, u8, u8, u8, u8, String, String)>;
+
+/// Classroom values that describe how a class is given instead of where, so classes that share
+/// them are not necessarily in the same room.
+const PLACEHOLDER_CLASSROOMS: [&str; 6] = [
+    "presencial",
+    "presnecial",
+    "virtual",
+    "vitual",
+    "asincr",
+    "laboratorio",
+];
+
+fn is_placeholder_classroom(classroom: &str) -> bool {
+    let classroom = classroom.trim().to_lowercase();
+    classroom.is_empty()
+        || PLACEHOLDER_CLASSROOMS
+            .iter()
+            .any(|placeholder| classroom.starts_with(placeholder))
+}
+
+/// The times and classrooms of a commission, or `None` when any of its classes has no actual
+/// classroom.
+fn class_signature(commission: &json_parser::SubjectCommission) -> Option<ClassSignature> {
+    let signature = commission
+        .course_commission_times
+        .0
+        .iter()
+        .map(|t| {
+            let classroom = t
+                .classroom
+                .clone()
+                .filter(|classroom| !is_placeholder_classroom(classroom))?;
+            Some((
+                t.day as u8,
+                t.span.start.hours,
+                t.span.start.minutes,
+                t.span.end.hours,
+                t.span.end.minutes,
+                t.building.clone(),
+                classroom,
+            ))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!signature.is_empty()).then(|| signature.into_iter().sorted().collect())
+}
+
+/// Subjects whose commissions have exactly the same times and classrooms as the commissions of
+/// another subject are the same class listed under several codes.
+fn find_cross_listings(parsed: &json_parser::SubjectCommissions) -> HashMap<Code, Vec<Code>> {
+    let mut signatures: HashMap<Code, Option<BTreeSet<ClassSignature>>> = HashMap::new();
+    for commission in &parsed.0 {
+        let signature = class_signature(commission);
+        let entry = signatures
+            .entry(commission.subject_code)
+            .or_insert_with(|| Some(BTreeSet::new()));
+        match (entry.as_mut(), signature) {
+            (Some(set), Some(signature)) => {
+                set.insert(signature);
+            }
+            _ => *entry = None,
+        }
+    }
+
+    let mut by_signatures: HashMap<BTreeSet<ClassSignature>, HashSet<Code>> = HashMap::new();
+    for (code, signatures) in signatures {
+        if let Some(signatures) = signatures {
+            by_signatures.entry(signatures).or_default().insert(code);
+        }
+    }
+
+    let mut cross_listings: HashMap<Code, Vec<Code>> = HashMap::new();
+    for codes in by_signatures.into_values().filter(|codes| codes.len() > 1) {
+        for (&code, &other) in codes.iter().tuple_combinations() {
+            cross_listings.entry(code).or_default().push(other);
+            cross_listings.entry(other).or_default().push(code);
+        }
+    }
+    cross_listings
+        .values_mut()
+        .for_each(|codes| codes.sort_by_key(|code| (code.high, code.low)));
+    cross_listings
+}
+
 fn map(
     parsed: json_parser::SubjectCommissions,
 ) -> Result<Vec<Arc<RefCell<Subject>>>, Box<dyn Error>> {
+    let mut cross_listings = find_cross_listings(&parsed);
     Ok(parsed
         .0
         .iter()
-        .group_by(|s| (s.subject_code, &s.subject_name))
+        .chunk_by(|s| (s.subject_code, &s.subject_name))
         .into_iter()
         .map(|((code, name), commissions)| {
             Arc::new_cyclic(|sub| {
@@ -54,6 +139,9 @@ fn map(
                                     ).collect_vec())
                             }
                         }),
+                        // A quota of zero means the source has no seat data for it.
+                        free_seats: (c.quota > 0)
+                            .then(|| c.quota.saturating_sub(c.enrolled_students)),
                     })
                     .collect_vec();
                 let credits = commissions[0]
@@ -70,6 +158,9 @@ fn map(
                     },
                     name: name.clone(),
                     credits,
+                    components: vec![],
+                    compatible_commissions: vec![],
+                    cross_listed: cross_listings.remove(&code).unwrap_or_default(),
                     commissions,
                 })
             })
@@ -91,3 +182,91 @@ pub fn load_from_string(string: &str) -> Result<Vec<Arc<RefCell<Subject>>>, Box<
     let parsed = serde_json::from_str(string)?;
     map(parsed)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commission(code: &str, name: &str, building: &str, classroom: &str) -> String {
+        format!(
+            r#"{{
+                "subjectCode": "{code}",
+                "subjectName": "Subject {code}",
+                "subjectType": "NORMAL",
+                "courseStart": "21/02/22",
+                "courseEnd": "31/12/22",
+                "commissionName": "{name}",
+                "commissionId": "1",
+                "quota": "30",
+                "enrolledStudents": "10",
+                "courseCommissionTimes": {{
+                    "day": "MONDAY",
+                    "classRoom": "{classroom}",
+                    "building": "{building}",
+                    "hourFrom": "10:00",
+                    "hourTo": "12:00"
+                }}
+            }}"#
+        )
+    }
+
+    #[test]
+    fn detect_cross_listed_subjects() {
+        let json = format!(
+            r#"{{"courseCommissions": {{"courseCommission": [{}]}}}}"#,
+            [
+                commission("10.01", "A", "SDT", "101"),
+                commission("10.02", "S", "SDT", "101"),
+                commission("10.03", "A", "SDT", "102"),
+                commission("10.04", "A", "SDT", "101"),
+                commission("10.04", "B", "SDT", "103"),
+                commission("10.05", "A", "External", "Presencial"),
+                commission("10.06", "A", "External", "Presencial"),
+                commission("10.07", "A", "External", "Virtual"),
+                commission("10.08", "A", "External", "Virtual"),
+            ]
+            .join(",")
+        );
+        let subjects = load_from_string(&json).unwrap();
+        let cross_listed = subjects
+            .iter()
+            .map(|s| s.borrow().cross_listed.clone())
+            .collect_vec();
+        assert_eq!(
+            cross_listed,
+            vec![
+                vec!["10.02".parse().unwrap()],
+                vec!["10.01".parse().unwrap()],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn free_seats_from_quota() {
+        let json = format!(
+            r#"{{"courseCommissions": {{"courseCommission": [{}]}}}}"#,
+            [
+                commission("10.01", "A", "SDT", "101"),
+                commission("10.01", "B", "SDT", "102")
+                    .replace(r#""quota": "30""#, r#""quota": "0""#),
+            ]
+            .join(",")
+        );
+        let subjects = load_from_string(&json).unwrap();
+        assert_eq!(
+            subjects[0]
+                .borrow()
+                .commissions
+                .iter()
+                .map(|c| c.free_seats)
+                .collect_vec(),
+            vec![Some(20), None]
+        );
+    }
+}
@@ -76,11 +76,11 @@ fn query_subject_commissions(
                         )
                     }),
                     subject: subject.clone(),
+                    free_seats: None,
                 })
             },
         )
         .unwrap()
-        .into_iter()
         .map(Result::unwrap)
         .collect()
 }
@@ -110,6 +110,9 @@ pub fn load() -> Result<Vec<Arc<RefCell<Subject>>>, Box<dyn Error>> {
                     code,
                     name,
                     credits,
+                    components: vec![],
+                    compatible_commissions: vec![],
+                    cross_listed: vec![],
                     commissions,
                 })
             }))
@@ -1,8 +1,8 @@
 use std::cmp::Ordering;
 
-use itertools::Itertools;
+use itertools::{iproduct, Itertools};
 
-use super::{collidable::Collidable, combinable::Combinable, task::Task};
+use super::{collidable::Collidable, combinable::Combinable, span::Span, task::Task};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Day<T> {
@@ -31,7 +31,7 @@ pub struct Day<T> {
 impl<T> Day<T> {
     pub fn new(mut tasks: Vec<Task<T>>) -> Day<T> {
         //assert!(tasks.iter().map(|task| task.span).is_monotonic_increasing());
-        tasks.sort_by(|a, b| a.span.cmp(&b.span));
+        tasks.sort_by_key(|task| task.span);
         let mut day = Day {
             tasks,
             has_collisions: false,
@@ -60,6 +60,13 @@ impl<T> Day<T> {
     pub fn has_collisions(&self) -> bool {
         self.has_collisions
     }
+
+    pub fn collisions_with<U>(&self, other: &Day<U>) -> Vec<(Span, Span)> {
+        iproduct!(self.tasks.iter(), other.tasks.iter())
+            .map(|(t1, t2)| (t1.span, t2.span))
+            .filter(|(s1, s2)| s1.collides(s2))
+            .collect()
+    }
 }
 
 impl<T: Clone> Combinable for Day<T> {
@@ -101,7 +108,6 @@ impl<T> Collidable for Day<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::span::Span;
     use crate::models::time::Time;
 
     //#[test]
@@ -4,9 +4,13 @@ mod combinable;
 pub use combinable::Combinable;
 mod day;
 pub use day::Day;
+mod overlap;
+pub use overlap::Overlap;
 mod span;
 pub use span::Span;
 mod subjects;
+#[cfg(test)]
+pub(crate) use subjects::test_support;
 pub use subjects::{Building, Code, Subject, SubjectCommision, TaskInfo};
 mod task;
 pub use task::Task;
@@ -0,0 +1,5 @@
+use super::{DaysOfTheWeek, Span};
+
+pub trait Overlap<Rhs = Self> {
+    fn overlaps(&self, other: &Rhs) -> Vec<(DaysOfTheWeek, Span)>;
+}
@@ -17,6 +17,12 @@ impl Span {
     pub fn duration(&self) -> u64 {
         self.end - self.start
     }
+
+    pub fn intersection(&self, other: &Span) -> Option<Span> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        (start < end).then(|| Span::new(start, end))
+    }
 }
 
 impl Display for Span {
@@ -66,4 +72,21 @@ mod tests {
             &Span::new(time3, time4)
         ),);
     }
+
+    #[test]
+    fn intersect_spans() {
+        let span = |a: &str, b: &str| Span::new(a.parse().unwrap(), b.parse().unwrap());
+        assert_eq!(
+            span("10:00", "12:00").intersection(&span("11:45", "13:00")),
+            Some(span("11:45", "12:00"))
+        );
+        assert_eq!(
+            span("10:00", "12:00").intersection(&span("10:30", "11:00")),
+            Some(span("10:30", "11:00"))
+        );
+        assert_eq!(
+            span("10:00", "12:00").intersection(&span("12:00", "13:00")),
+            None
+        );
+    }
 }
@@ -1,7 +1,7 @@
 use itertools::Itertools;
 pub use json_parser::Code;
 
-use crate::models::{Collidable, Week};
+use crate::models::{Collidable, Combinable, DaysOfTheWeek, Overlap, Span, Week};
 use std::{
     cell::RefCell,
     collections::HashSet,
@@ -10,7 +10,6 @@ use std::{
     ops::{Add, BitOr},
     sync::Weak,
 };
-pub use std::{error::Error, rc::Rc, str::FromStr, string::ParseError};
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Building {
@@ -33,7 +32,12 @@ impl Eq for TaskInfo {}
 
 impl Hash for TaskInfo {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.buildings.iter().for_each(|b| b.hash(state))
+        // Sorted, since equal sets can iterate in different orders.
+        self.buildings
+            .iter()
+            .map(|b| &b.name)
+            .sorted()
+            .for_each(|name| name.hash(state))
     }
 }
 
@@ -43,8 +47,8 @@ impl Add for TaskInfo {
     fn add(self, rhs: Self) -> Self::Output {
         assert!(self.subject.ptr_eq(&rhs.subject));
         let mut new_building = HashSet::new();
-        new_building.extend(self.buildings.into_iter());
-        new_building.extend(rhs.buildings.into_iter());
+        new_building.extend(self.buildings);
+        new_building.extend(rhs.buildings);
         TaskInfo {
             subject: self.subject,
             buildings: new_building,
@@ -57,6 +61,8 @@ pub struct SubjectCommision {
     pub names: Vec<String>,
     pub subject: Weak<RefCell<Subject>>,
     pub schedule: Week<TaskInfo>,
+    /// Seats left according to the source data, if it has them.
+    pub free_seats: Option<u64>,
 }
 impl Display for SubjectCommision {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -73,6 +79,11 @@ impl Collidable for SubjectCommision {
         self.schedule.collides(&other.schedule)
     }
 }
+impl Overlap for SubjectCommision {
+    fn overlaps(&self, other: &Self) -> Vec<(DaysOfTheWeek, Span)> {
+        self.schedule.overlaps(&other.schedule)
+    }
+}
 impl Hash for SubjectCommision {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.names.hash(state);
@@ -97,6 +108,8 @@ impl BitOr<&SubjectCommision> for &SubjectCommision {
             names,
             subject: self.subject.clone(),
             schedule: self.schedule.clone(),
+            // A student can take a seat in any of the merged commissions.
+            free_seats: self.free_seats.zip(rhs.free_seats).map(|(a, b)| a + b),
         }
     }
 }
@@ -107,6 +120,15 @@ pub struct Subject {
     pub name: String,
     pub commissions: Vec<SubjectCommision>,
     pub credits: u8,
+    /// Commission names grouped by component (e.g. lectures and labs). A student takes one
+    /// commission of every component. Empty when each commission is a complete alternative.
+    pub components: Vec<Vec<String>>,
+    /// Pairs of commission names from different components that may be taken together. Two
+    /// components without any pair between them can be combined freely.
+    pub compatible_commissions: Vec<(String, String)>,
+    /// Other subjects that are the same class as this one, at the same times and classroom,
+    /// listed under a different code.
+    pub cross_listed: Vec<Code>,
 }
 
 impl Display for Subject {
@@ -121,6 +143,81 @@ impl Subject {
             .find(|com| com.names.iter().any(|name| name == id))
     }
 
+    /// The component the commission with this name belongs to, if any.
+    pub fn component_of_name(&self, name: &str) -> Option<usize> {
+        self.components
+            .iter()
+            .position(|component| component.iter().any(|n| n == name))
+    }
+
+    fn component_of(&self, commission: &SubjectCommision) -> Option<usize> {
+        self.components
+            .iter()
+            .position(|component| component.iter().any(|name| commission.names.contains(name)))
+    }
+
+    fn linked(&self, a: &[String], b: &[String]) -> bool {
+        self.compatible_commissions
+            .iter()
+            .any(|(x, y)| (a.contains(x) && b.contains(y)) || (a.contains(y) && b.contains(x)))
+    }
+
+    fn compatible(&self, a: &SubjectCommision, b: &SubjectCommision) -> bool {
+        let names_of = |component: usize| self.components[component].as_slice();
+        let (Some(component_a), Some(component_b)) = (self.component_of(a), self.component_of(b))
+        else {
+            return true;
+        };
+        let has_rules = self.linked(names_of(component_a), names_of(component_b));
+        !has_rules || self.linked(&a.names, &b.names)
+    }
+
+    /// The alternatives a student can actually choose from: one commission of every component
+    /// combined into a single commission. Commissions outside of every component are kept as
+    /// they are.
+    pub fn bundles(&self) -> Vec<SubjectCommision> {
+        if self.components.is_empty() {
+            return self.commissions.clone();
+        }
+
+        let by_component = (0..self.components.len())
+            .map(|component| {
+                self.commissions
+                    .iter()
+                    .filter(|c| self.component_of(c) == Some(component))
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        by_component
+            .into_iter()
+            .multi_cartesian_product()
+            .filter(|bundle| {
+                bundle
+                    .iter()
+                    .tuple_combinations()
+                    .all(|(a, b)| self.compatible(a, b) && !a.schedule.collides(&b.schedule))
+            })
+            .map(|bundle| SubjectCommision {
+                names: bundle.iter().flat_map(|c| c.names.clone()).collect(),
+                subject: bundle[0].subject.clone(),
+                schedule: bundle
+                    .iter()
+                    .map(|c| c.schedule.clone())
+                    .reduce(|a, b| a.combine(&b))
+                    .unwrap(),
+                // A bundle is as full as its fullest known part.
+                free_seats: bundle.iter().filter_map(|c| c.free_seats).min(),
+            })
+            .chain(
+                self.commissions
+                    .iter()
+                    .filter(|c| self.component_of(c).is_none())
+                    .cloned(),
+            )
+            .collect()
+    }
+
     pub fn optimize(&mut self) {
         for com in self.commissions.iter_mut() {
             com.schedule.simplify();
@@ -138,7 +235,7 @@ impl Subject {
                 .into_group_map_by(|com| com.schedule.clone());
             eq_groups
                 .into_values()
-                .map(|group| group.into_iter().reduce(|a, b| (&a | &b)).unwrap())
+                .map(|group| group.into_iter().reduce(|a, b| &a | &b).unwrap())
                 .collect_vec()
         };
 
@@ -146,6 +243,167 @@ impl Subject {
     }
 }
 
+/// Builds subjects for tests without spelling out every field and back reference.
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+    use crate::models::{Day, Task, Time};
+    use std::sync::Arc;
+
+    pub(crate) fn class(day: DaysOfTheWeek, start: &str, end: &str) -> (DaysOfTheWeek, Span) {
+        (
+            day,
+            Span::new(start.parse::<Time>().unwrap(), end.parse().unwrap()),
+        )
+    }
+
+    /// The subject's entry as given to an [`OptionGenerator`](crate::option_generator::OptionGenerator).
+    pub(crate) fn entry(subject: &Arc<RefCell<Subject>>) -> (Code, Vec<SubjectCommision>) {
+        let subject = subject.borrow();
+        (subject.code, subject.commissions.clone())
+    }
+
+    /// An option as the names of its commissions, with `-` for subjects left out.
+    pub(crate) fn label(option: &[Option<SubjectCommision>]) -> String {
+        option
+            .iter()
+            .map(|c| c.as_ref().map_or("-".to_owned(), |c| c.names.join("")))
+            .collect()
+    }
+
+    struct CommissionSpec {
+        names: Vec<String>,
+        classes: Vec<(DaysOfTheWeek, Span)>,
+        buildings: Vec<String>,
+        free_seats: Option<u64>,
+    }
+
+    pub(crate) struct SubjectBuilder {
+        code: Code,
+        credits: u8,
+        commissions: Vec<CommissionSpec>,
+        components: Vec<Vec<String>>,
+        compatible_commissions: Vec<(String, String)>,
+    }
+
+    impl SubjectBuilder {
+        pub(crate) fn new(code: &str) -> Self {
+            Self {
+                code: code.parse().unwrap(),
+                credits: 3,
+                commissions: vec![],
+                components: vec![],
+                compatible_commissions: vec![],
+            }
+        }
+
+        pub(crate) fn credits(&mut self, credits: u8) -> &mut Self {
+            self.credits = credits;
+            self
+        }
+
+        pub(crate) fn commission(
+            &mut self,
+            name: &str,
+            classes: &[(DaysOfTheWeek, Span)],
+        ) -> &mut Self {
+            self.commissions.push(CommissionSpec {
+                names: vec![name.to_owned()],
+                classes: classes.to_vec(),
+                buildings: vec![],
+                free_seats: None,
+            });
+            self
+        }
+
+        /// One commission per class, named after its position: "0", "1"...
+        pub(crate) fn commissions(&mut self, classes: &[(DaysOfTheWeek, Span)]) -> &mut Self {
+            for class in classes {
+                let name = self.commissions.len().to_string();
+                self.commission(&name, &[*class]);
+            }
+            self
+        }
+
+        fn last(&mut self) -> &mut CommissionSpec {
+            self.commissions.last_mut().unwrap()
+        }
+
+        /// Adds a name to the last commission, as if merged by [`Subject::optimize`].
+        pub(crate) fn also_named(&mut self, name: &str) -> &mut Self {
+            self.last().names.push(name.to_owned());
+            self
+        }
+
+        /// Puts every class of the last commission in `building`.
+        pub(crate) fn building(&mut self, building: &str) -> &mut Self {
+            self.last().buildings.push(building.to_owned());
+            self
+        }
+
+        pub(crate) fn free_seats(&mut self, seats: u64) -> &mut Self {
+            self.last().free_seats = Some(seats);
+            self
+        }
+
+        pub(crate) fn components(&mut self, components: &[&[&str]]) -> &mut Self {
+            self.components = components
+                .iter()
+                .map(|names| names.iter().map(|n| n.to_string()).collect())
+                .collect();
+            self
+        }
+
+        pub(crate) fn compatible(&mut self, a: &str, b: &str) -> &mut Self {
+            self.compatible_commissions
+                .push((a.to_owned(), b.to_owned()));
+            self
+        }
+
+        pub(crate) fn build(&self) -> Arc<RefCell<Subject>> {
+            Arc::new_cyclic(|subject| {
+                let commissions = self
+                    .commissions
+                    .iter()
+                    .map(|spec| {
+                        let mut schedule = Week::empty();
+                        for (day, span) in &spec.classes {
+                            let task = Task::new(
+                                *span,
+                                TaskInfo {
+                                    subject: subject.clone(),
+                                    buildings: spec
+                                        .buildings
+                                        .iter()
+                                        .map(|name| Building { name: name.clone() })
+                                        .collect(),
+                                },
+                            );
+                            schedule.days[*day] =
+                                schedule.days[*day].combine(&Day::new(vec![task]));
+                        }
+                        SubjectCommision {
+                            names: spec.names.clone(),
+                            subject: subject.clone(),
+                            schedule,
+                            free_seats: spec.free_seats,
+                        }
+                    })
+                    .collect();
+                RefCell::new(Subject {
+                    code: self.code,
+                    name: self.code.to_string(),
+                    commissions,
+                    credits: self.credits,
+                    components: self.components.clone(),
+                    compatible_commissions: self.compatible_commissions.clone(),
+                    cross_listed: vec![],
+                })
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{cell::RefCell, sync::Arc, vec};
@@ -230,22 +488,28 @@ mod tests {
                         subject: subject.clone(),
                         names: vec!["Com A".to_owned()],
                         schedule: week_1(subject),
+                        free_seats: None,
                     },
                     SubjectCommision {
                         subject: subject.clone(),
                         names: vec!["Com B".to_owned()],
                         schedule: week_3(subject),
+                        free_seats: None,
                     },
                     SubjectCommision {
                         subject: subject.clone(),
                         names: vec!["Com C".to_owned()],
                         schedule: week_2(subject),
+                        free_seats: None,
                     },
                 ],
 
                 code: "00.00".parse().unwrap(),
                 name: "Nombre".to_owned(),
                 credits: 3,
+                components: vec![],
+                compatible_commissions: vec![],
+                cross_listed: vec![],
             })
         });
 
@@ -260,14 +524,19 @@ mod tests {
                         subject: subject.clone(),
                         names: vec!["Com B".to_owned()],
                         schedule: week_3(subject),
+                        free_seats: None,
                     },
                     SubjectCommision {
                         subject: subject.clone(),
                         names: vec!["Com A".to_owned(), "Com C".to_owned()],
                         schedule: week_1(subject),
+                        free_seats: None,
                     },
                 ],
                 credits: 3,
+                components: vec![],
+                compatible_commissions: vec![],
+                cross_listed: vec![],
             })
         });
 
@@ -286,4 +555,44 @@ mod tests {
                 .collect::<HashSet<_>>(),
         );
     }
+
+    #[test]
+    fn subject_bundles() {
+        let monday = |start, end| [test_support::class(DaysOfTheWeek::Monday, start, end)];
+        let subject = test_support::SubjectBuilder::new("00.00")
+            .commission("T1", &monday("08:00", "10:00"))
+            .commission("T2", &monday("14:00", "16:00"))
+            .free_seats(4)
+            .commission("L1", &monday("10:00", "12:00"))
+            .commission("L2", &monday("09:00", "11:00"))
+            .commission("L3", &monday("16:00", "18:00"))
+            .free_seats(2)
+            .commission("X", &monday("18:00", "20:00"))
+            .components(&[&["T1", "T2"], &["L1", "L2", "L3"]])
+            .compatible("T1", "L1")
+            .compatible("T1", "L2")
+            .compatible("L3", "T2")
+            .build();
+
+        let bundles = subject.borrow().bundles();
+        assert_eq!(
+            bundles.iter().map(|c| c.names.clone()).collect_vec(),
+            vec![
+                vec!["T1".to_owned(), "L1".to_owned()],
+                vec!["T2".to_owned(), "L3".to_owned()],
+                vec!["X".to_owned()],
+            ]
+        );
+        assert_eq!(
+            bundles[0].schedule.days[DaysOfTheWeek::Monday].tasks.len(),
+            2
+        );
+        assert_eq!(
+            bundles.iter().map(|c| c.free_seats).collect_vec(),
+            vec![None, Some(2), None]
+        );
+
+        subject.borrow_mut().compatible_commissions.clear();
+        assert_eq!(subject.borrow().bundles().len(), 6);
+    }
 }
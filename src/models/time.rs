@@ -9,7 +9,6 @@ macro_rules! t {
     };
 }
 
-
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Time {
     pub hour: u8,
@@ -22,6 +21,10 @@ impl Time {
         assert!(minutes < 60);
         Time { hour, minutes }
     }
+
+    pub fn minutes_since_midnight(self) -> u64 {
+        self.hour as u64 * 60 + self.minutes as u64
+    }
 }
 
 impl Debug for Time {
@@ -51,7 +54,7 @@ impl Sub for Time {
     type Output = u64;
 
     fn sub(self, rhs: Self) -> Self::Output {
-        (self.hour as u64 * 60 + self.minutes as u64) - (rhs.hour as u64 * 60 + rhs.minutes as u64)
+        self.minutes_since_midnight() - rhs.minutes_since_midnight()
     }
 }
 
@@ -2,7 +2,7 @@ use std::ops::Add;
 
 use super::combinable::Combinable;
 use super::Span;
-use super::{collidable::Collidable, Task};
+use super::{collidable::Collidable, Overlap, Task};
 use crate::models::day::Day;
 use enum_map::{enum_map, Enum, EnumMap};
 #[cfg(feature = "json")]
@@ -10,7 +10,7 @@ use serde::Serialize;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-#[derive(Debug, Enum, Clone, Copy, EnumIter)]
+#[derive(Debug, Enum, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
 #[cfg_attr(feature = "json", derive(Serialize))]
 #[cfg_attr(feature = "json", serde(rename_all = "lowercase"))]
 pub enum DaysOfTheWeek {
@@ -38,6 +38,17 @@ impl<T> Week<T> {
             _ => Day::empty()
         })
     }
+
+    pub fn collisions_with<U>(&self, other: &Week<U>) -> Vec<(DaysOfTheWeek, Span, Span)> {
+        DaysOfTheWeek::iter()
+            .flat_map(|day| {
+                self.days[day]
+                    .collisions_with(&other.days[day])
+                    .into_iter()
+                    .map(move |(s1, s2)| (day, s1, s2))
+            })
+            .collect()
+    }
 }
 
 impl<T: Add<Output = T> + Clone> Week<T> {
@@ -76,6 +87,15 @@ impl<T: Clone> Combinable for Week<T> {
     }
 }
 
+impl<T, U> Overlap<Week<U>> for Week<T> {
+    fn overlaps(&self, other: &Week<U>) -> Vec<(DaysOfTheWeek, Span)> {
+        self.collisions_with(other)
+            .into_iter()
+            .filter_map(|(day, s1, s2)| Some((day, s1.intersection(&s2)?)))
+            .collect()
+    }
+}
+
 impl<T> Collidable for Week<T> {
     fn collides(&self, other: &Self) -> bool {
         for day in DaysOfTheWeek::iter() {
@@ -165,4 +185,26 @@ mod tests {
             })
         )
     }
+
+    #[test]
+    fn week_collisions_with() {
+        let span_a = Span::new(t!("10:00"), t!("12:00"));
+        let span_b = Span::new(t!("11:00"), t!("13:00"));
+        let span_c = Span::new(t!("12:00"), t!("14:00"));
+        let week_1 = Week::new(enum_map! {
+            DaysOfTheWeek::Monday => Day::new(vec![Task::new(span_a, ())]),
+            DaysOfTheWeek::Friday => Day::new(vec![Task::new(span_a, ())]),
+            _ => Day::empty()
+        });
+        let week_2 = Week::new(enum_map! {
+            DaysOfTheWeek::Monday => Day::new(vec![Task::new(span_b, ())]),
+            DaysOfTheWeek::Friday => Day::new(vec![Task::new(span_c, ())]),
+            _ => Day::empty()
+        });
+        let collisions = week_1.collisions_with(&week_2);
+        assert_eq!(collisions.len(), 1);
+        assert!(
+            matches!(collisions[0], (DaysOfTheWeek::Monday, a, b) if a == span_a && b == span_b)
+        );
+    }
 }
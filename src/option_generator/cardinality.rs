@@ -0,0 +1,46 @@
+use super::{Chosen, Group};
+use core::hash::Hash;
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+/// Limits how many subjects out of `keys` an option may take, e.g. "exactly 2 of these 6
+/// electives".
+#[derive(Debug, Clone)]
+pub struct Cardinality<K> {
+    pub keys: HashSet<K>,
+    pub range: RangeInclusive<usize>,
+}
+
+impl<K: Hash + Eq> Cardinality<K> {
+    pub fn new(keys: impl IntoIterator<Item = K>, range: RangeInclusive<usize>) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+            range,
+        }
+    }
+
+    pub fn exactly(keys: impl IntoIterator<Item = K>, count: usize) -> Self {
+        Self::new(keys, count..=count)
+    }
+
+    pub fn at_least(keys: impl IntoIterator<Item = K>, count: usize) -> Self {
+        Self::new(keys, count..=usize::MAX)
+    }
+
+    pub fn at_most(keys: impl IntoIterator<Item = K>, count: usize) -> Self {
+        Self::new(keys, 0..=count)
+    }
+
+    /// Whether a partial choice can still be completed into one that satisfies the range.
+    pub(super) fn admits<T>(&self, chosen: &Chosen<K, T>, pending: &[(K, Group<T>)]) -> bool {
+        let chosen = chosen
+            .iter()
+            .filter(|(key, _, val)| val.is_some() && self.keys.contains(key))
+            .count();
+        let pending = pending
+            .iter()
+            .filter(|(key, group)| self.keys.contains(key) && !group.items.is_empty())
+            .count();
+        chosen <= *self.range.end() && chosen + pending >= *self.range.start()
+    }
+}
@@ -0,0 +1,76 @@
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+/// Position of a generated option in the search order. Resuming from a cursor yields the
+/// options that come right after it, as long as the generator is built from the same inputs.
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json", serde(try_from = "String", into = "String"))]
+pub struct Cursor(pub(super) Vec<usize>);
+
+impl Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.0
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join("-")
+        )
+    }
+}
+
+impl Debug for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cursor: \"{self}\"")
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = Box<dyn Error>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(Cursor(vec![]));
+        }
+        Ok(Cursor(
+            s.split('-')
+                .map(str::parse)
+                .collect::<Result<Vec<_>, _>>()?,
+        ))
+    }
+}
+
+impl TryFrom<String> for Cursor {
+    type Error = Box<dyn Error>;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Cursor> for String {
+    fn from(cursor: Cursor) -> Self {
+        cursor.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trip() {
+        let cursor = Cursor(vec![0, 12, 3]);
+        assert_eq!(cursor.to_string(), "0-12-3");
+        assert_eq!("0-12-3".parse::<Cursor>().unwrap(), cursor);
+        assert_eq!("".parse::<Cursor>().unwrap(), Cursor(vec![]));
+        assert!("0-a".parse::<Cursor>().is_err());
+    }
+}
@@ -0,0 +1,101 @@
+use crate::models::{DaysOfTheWeek, SubjectCommision};
+use enum_map::EnumMap;
+use std::iter;
+
+fn days_with_classes(option: &[Option<SubjectCommision>]) -> EnumMap<DaysOfTheWeek, bool> {
+    let mut days = EnumMap::default();
+    for com in option.iter().flatten() {
+        for (day, tasks) in &com.schedule.days {
+            days[day] |= !tasks.tasks.is_empty();
+        }
+    }
+    days
+}
+
+/// How different two options of the same generator are: two points for every subject only one
+/// of them takes, one for every subject taken in different commissions and one for every day
+/// only one of them has classes on.
+pub fn schedule_distance(a: &[Option<SubjectCommision>], b: &[Option<SubjectCommision>]) -> u32 {
+    let subjects: u32 = iter::zip(a, b)
+        .map(|pair| match pair {
+            (Some(a), Some(b)) if a != b => 1,
+            (Some(_), None) | (None, Some(_)) => 2,
+            _ => 0,
+        })
+        .sum();
+    let (days_a, days_b) = (days_with_classes(a), days_with_classes(b));
+    let days = days_a
+        .values()
+        .zip(days_b.values())
+        .filter(|(a, b)| a != b)
+        .count() as u32;
+    subjects + days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{class, SubjectBuilder};
+    use crate::models::Span;
+    use crate::option_generator::{OptionGenerator, OptionIterator};
+    use crate::t;
+    use itertools::Itertools;
+
+    #[test]
+    fn most_diverse_spans() {
+        let sa = Span::new(t!("00:00"), t!("01:00"));
+        let sb = Span::new(t!("01:00"), t!("02:00"));
+        let sc = Span::new(t!("02:00"), t!("03:00"));
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![("0", vec![sa, sc])])
+            .set_optional(vec![("1", vec![sa, sb, sc]), ("2", vec![sa, sb])]);
+        let distance = |a: &[Option<Span>], b: &[Option<Span>]| {
+            iter::zip(a, b).filter(|(a, b)| a != b).count() as u32
+        };
+
+        assert_eq!(
+            generator.clone().generate().most_diverse(3, distance),
+            vec![
+                vec![Some(sa), Some(sb), None],
+                vec![Some(sc), Some(sa), Some(sb)],
+                vec![Some(sa), Some(sc), Some(sb)],
+            ]
+        );
+        assert_eq!(
+            generator
+                .clone()
+                .generate()
+                .most_diverse(100, distance)
+                .len(),
+            generator.generate().count()
+        );
+    }
+
+    #[test]
+    fn schedule_distance_test() {
+        let subject = SubjectBuilder::new("01.01")
+            .commission("A", &[class(DaysOfTheWeek::Monday, "10:00", "12:00")])
+            .commission("B", &[class(DaysOfTheWeek::Friday, "10:00", "12:00")])
+            .build();
+        let coms = subject.borrow().commissions.clone();
+
+        let a = [Some(coms[0].clone()), None];
+        let b = [Some(coms[1].clone()), None];
+        let c = [Some(coms[0].clone()), Some(coms[1].clone())];
+        assert_eq!(schedule_distance(&a, &a), 0);
+        assert_eq!(schedule_distance(&a, &b), 3);
+        assert_eq!(schedule_distance(&a, &c), 3);
+        assert_eq!(schedule_distance(&b, &c), 4);
+        assert_eq!(
+            [a, b, c]
+                .into_iter()
+                .map(Vec::from)
+                .most_diverse(2, schedule_distance)
+                .into_iter()
+                .map(|option| option.iter().flatten().map(|c| c.names[0].clone()).join(""))
+                .collect_vec(),
+            vec!["A", "B"]
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use crate::models::{Combinable, SubjectCommision, TaskInfo, Week};
+
+/// Options that are the same schedule, e.g. because two subjects swap identically timed
+/// commissions. Options are kept in generation order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equivalent<T> {
+    pub options: Vec<Vec<Option<T>>>,
+}
+
+impl<T> Equivalent<T> {
+    pub fn representative(&self) -> &Vec<Option<T>> {
+        &self.options[0]
+    }
+}
+
+/// Which subjects were chosen together with the week they add up to.
+pub fn schedule_key(option: &[Option<SubjectCommision>]) -> (Vec<bool>, Week<TaskInfo>) {
+    (
+        option.iter().map(Option::is_some).collect(),
+        option
+            .iter()
+            .flatten()
+            .fold(Week::empty(), |week, com| week.combine(&com.schedule)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{class, entry, SubjectBuilder};
+    use crate::models::{DaysOfTheWeek, Span};
+    use crate::option_generator::{OptionGenerator, OptionIterator};
+    use crate::t;
+    use itertools::Itertools;
+
+    #[test]
+    fn collapse_symmetric_options() {
+        let sa = Span::new(t!("08:00"), t!("10:00"));
+        let sb = Span::new(t!("10:00"), t!("12:00"));
+        let sc = Span::new(t!("12:00"), t!("14:00"));
+        let monday = |spans: &[Span]| {
+            spans
+                .iter()
+                .map(|span| (DaysOfTheWeek::Monday, *span))
+                .collect_vec()
+        };
+        let subjects = [
+            SubjectBuilder::new("01.01")
+                .commissions(&monday(&[sa, sb]))
+                .build(),
+            SubjectBuilder::new("01.02")
+                .commissions(&monday(&[sb, sa, sc]))
+                .build(),
+        ];
+
+        let mut generator = OptionGenerator::default();
+        generator.set_mandatory(subjects.iter().map(entry).collect());
+        let groups = generator.generate().collapse_equivalent(schedule_key);
+
+        let names = |option: &Vec<Option<SubjectCommision>>| {
+            option.iter().flatten().map(|c| c.names.join("")).join(" ")
+        };
+        assert_eq!(
+            groups
+                .iter()
+                .map(|group| group.options.iter().map(names).collect_vec())
+                .collect_vec(),
+            vec![vec!["0 0", "1 1"], vec!["0 2"], vec!["1 2"],]
+        );
+        assert_eq!(names(groups[0].representative()), "0 0");
+    }
+
+    #[test]
+    fn collapse_with_several_buildings() {
+        let subject = |code: &str, times: [(&str, &str); 2]| {
+            let mut builder = SubjectBuilder::new(code);
+            for (i, (start, end)) in times.into_iter().enumerate() {
+                builder.commission(&i.to_string(), &[class(DaysOfTheWeek::Monday, start, end)]);
+                for building in ["A", "B", "C", "D"] {
+                    builder.building(building);
+                }
+            }
+            builder.build()
+        };
+        let subjects = [
+            subject("01.01", [("08:00", "10:00"), ("10:00", "12:00")]),
+            subject("01.02", [("10:00", "12:00"), ("08:00", "10:00")]),
+        ];
+
+        let mut generator = OptionGenerator::default();
+        generator.set_mandatory(subjects.iter().map(entry).collect());
+        let groups = generator.generate().collapse_equivalent(schedule_key);
+        assert_eq!(
+            groups.iter().map(|group| group.options.len()).collect_vec(),
+            vec![2]
+        );
+    }
+}
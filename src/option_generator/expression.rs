@@ -0,0 +1,330 @@
+use super::filters::{
+    And, BoxedFilter, CreditCount, DailyLoad, FreeDays, FreeSeats, IdleGaps, Not, NotBefore, Or,
+    SubjectCount, TimeWindow,
+};
+use crate::models::{DaysOfTheWeek, Span, SubjectCommision, Time};
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+
+type Filter = BoxedFilter<'static, SubjectCommision>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Byte offset of the offending token in the expression.
+    pub position: usize,
+    pub token: String,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.token.as_str() {
+            "" => write!(f, "{} at the end of the expression", self.message),
+            token => write!(
+                f,
+                "{} at position {}: {token:?}",
+                self.message, self.position
+            ),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token<'_>>, ParseError> {
+    let mut tokens = vec![];
+    let mut rest = input.char_indices().peekable();
+    while let Some((position, c)) = rest.next() {
+        let mut end = position + c.len_utf8();
+        let mut take_while = |accept: fn(char) -> bool| {
+            while let Some((i, c)) = rest.next_if(|(_, c)| accept(*c)) {
+                end = i + c.len_utf8();
+            }
+        };
+        match c {
+            c if c.is_whitespace() => continue,
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                take_while(|c| c.is_ascii_alphanumeric() || c == '_')
+            }
+            c if c.is_ascii_digit() => take_while(|c| c.is_ascii_digit() || c == ':'),
+            '(' | ')' | ',' => {}
+            '.' | '>' | '<' => {
+                let second = if c == '.' { '.' } else { '=' };
+                match rest.next_if(|(_, c)| *c == second) {
+                    Some(_) => end += 1,
+                    None => {
+                        return Err(ParseError {
+                            position,
+                            token: c.to_string(),
+                            message: format!("Expected `{c}{second}`"),
+                        })
+                    }
+                }
+            }
+            c => {
+                return Err(ParseError {
+                    position,
+                    token: c.to_string(),
+                    message: "Unexpected character".to_owned(),
+                })
+            }
+        }
+        tokens.push(Token {
+            text: &input[position..end],
+            position,
+        });
+    }
+    tokens.push(Token {
+        text: "",
+        position: input.len(),
+    });
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    next: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Token<'a> {
+        self.tokens[self.next]
+    }
+
+    fn advance(&mut self) -> Token<'a> {
+        let token = self.peek();
+        self.next = (self.next + 1).min(self.tokens.len() - 1);
+        token
+    }
+
+    fn error(token: Token, message: impl Into<String>) -> ParseError {
+        ParseError {
+            position: token.position,
+            token: token.text.to_owned(),
+            message: message.into(),
+        }
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        let matches = self.peek().text == text;
+        if matches {
+            self.advance();
+        }
+        matches
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), ParseError> {
+        match self.eat(text) {
+            true => Ok(()),
+            false => Err(Self::error(self.peek(), format!("Expected `{text}`"))),
+        }
+    }
+
+    fn or(&mut self) -> Result<Filter, ParseError> {
+        let mut filter = self.and()?;
+        while self.eat("or") {
+            filter = BoxedFilter::new(Or(filter, self.and()?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<Filter, ParseError> {
+        let mut filter = self.unary()?;
+        while self.eat("and") {
+            filter = BoxedFilter::new(And(filter, self.unary()?));
+        }
+        Ok(filter)
+    }
+
+    fn unary(&mut self) -> Result<Filter, ParseError> {
+        match self.eat("not") {
+            true => Ok(BoxedFilter::new(Not(self.unary()?))),
+            false => self.atom(),
+        }
+    }
+
+    fn atom(&mut self) -> Result<Filter, ParseError> {
+        let token = self.advance();
+        Ok(match token.text {
+            "(" => {
+                let filter = self.or()?;
+                self.expect(")")?;
+                filter
+            }
+            "credits" => {
+                self.expect("in")?;
+                BoxedFilter::new(CreditCount::new(self.range()?))
+            }
+            "subjects" => {
+                self.expect("in")?;
+                BoxedFilter::new(SubjectCount::new(self.range()?))
+            }
+            "free" => {
+                self.expect("(")?;
+                let mut days = vec![self.day()?];
+                while self.eat(",") {
+                    days.push(self.day()?);
+                }
+                self.expect(")")?;
+                BoxedFilter::new(FreeDays::on(days))
+            }
+            "free_days" => {
+                self.expect(">=")?;
+                BoxedFilter::new(FreeDays::at_least(self.number()?))
+            }
+            "start" => {
+                self.expect(">=")?;
+                BoxedFilter::new(NotBefore(self.time()?))
+            }
+            "end" => {
+                self.expect("<=")?;
+                let time_token = self.peek();
+                let time = self.time()?;
+                if time == Time::new(0, 0) {
+                    return Err(Self::error(time_token, "Expected a time after 00:00"));
+                }
+                let mut window = TimeWindow::new();
+                window.set_days(DaysOfTheWeek::iter(), Span::new(Time::new(0, 0), time));
+                BoxedFilter::new(window)
+            }
+            "seats" => {
+                self.expect(">=")?;
+                BoxedFilter::new(FreeSeats(self.number()?))
+            }
+            "gap" => {
+                self.expect("<=")?;
+                let mut filter = IdleGaps::new();
+                filter.set_max_gap(self.number()?);
+                BoxedFilter::new(filter)
+            }
+            "idle" => {
+                self.expect("<=")?;
+                let mut filter = IdleGaps::new();
+                filter.set_max_per_week(self.number()?);
+                BoxedFilter::new(filter)
+            }
+            "daily" => {
+                self.expect("<=")?;
+                let mut filter = DailyLoad::new();
+                filter.set_max_minutes(self.number()?);
+                BoxedFilter::new(filter)
+            }
+            _ => return Err(Self::error(token, "Expected a constraint")),
+        })
+    }
+
+    fn number<N: FromStr>(&mut self) -> Result<N, ParseError> {
+        let token = self.advance();
+        token
+            .text
+            .parse()
+            .map_err(|_| Self::error(token, "Expected a number"))
+    }
+
+    /// Ranges include both ends, as in `credits in 20..30`.
+    fn range(&mut self) -> Result<RangeInclusive<u32>, ParseError> {
+        let start = self.number()?;
+        self.expect("..")?;
+        let end_token = self.peek();
+        let end = self.number()?;
+        if end < start {
+            return Err(Self::error(end_token, "Range ends before it starts"));
+        }
+        Ok(start..=end)
+    }
+
+    fn time(&mut self) -> Result<Time, ParseError> {
+        let token = self.advance();
+        token
+            .text
+            .parse::<Time>()
+            .ok()
+            .filter(|time| time.minutes < 60 && (time.hour < 24 || *time == Time::new(24, 0)))
+            .ok_or_else(|| Self::error(token, "Expected a time like 09:00"))
+    }
+
+    fn day(&mut self) -> Result<DaysOfTheWeek, ParseError> {
+        let token = self.advance();
+        DaysOfTheWeek::iter()
+            .find(|day| format!("{day:?}").eq_ignore_ascii_case(token.text))
+            .ok_or_else(|| Self::error(token, "Expected a day of the week"))
+    }
+}
+
+/// Parses a constraint expression such as
+/// `credits in 20..30 and subjects in 4..5 and free(friday) and start >= 09:00`.
+///
+/// Constraints are `credits in A..B`, `subjects in A..B`, `free(DAY, ...)`, `free_days >= N`
+/// (counting Monday to Saturday), `start >= HH:MM`, `end <= HH:MM`, `seats >= N`, and `gap`,
+/// `idle` (per week) or `daily` `<= MINUTES`. They combine with `and`, `or`, `not` and
+/// parentheses.
+pub fn parse_filter(expression: &str) -> Result<Filter, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(expression)?,
+        next: 0,
+    };
+    let filter = parser.or()?;
+    match parser.peek().text {
+        "" => Ok(filter),
+        _ => Err(Parser::error(parser.peek(), "Unexpected token")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{class, SubjectBuilder};
+    use crate::option_generator::filters::ChoiceFilter;
+
+    #[test]
+    fn parse_and_filter() {
+        let subject = SubjectBuilder::new("01.01")
+            .credits(6)
+            .commission("A", &[class(DaysOfTheWeek::Monday, "08:00", "10:00")])
+            .free_seats(3)
+            .build();
+        let option = vec![Some(subject.borrow().commissions[0].clone()), None];
+        let accepts = |expression: &str| parse_filter(expression).unwrap().filter(&option);
+
+        assert!(accepts(
+            "credits in 6..8 and subjects in 1..1 and free(friday, Sunday)"
+        ));
+        assert!(!accepts("credits in 20..30 and subjects in 1..1"));
+        assert!(!accepts("start >= 09:00"));
+        assert!(accepts("not start >= 09:00 and end <= 10:00"));
+        assert!(accepts("(free(monday) or seats >= 3) and free_days >= 5"));
+        assert!(!accepts("free(monday) or seats >= 4"));
+        assert!(accepts("gap <= 0 and idle <= 0 and daily <= 120"));
+    }
+
+    #[test]
+    fn errors_point_at_the_token() {
+        let error = |expression: &str| {
+            let error = parse_filter(expression).err().unwrap();
+            (error.position, error.token)
+        };
+        assert_eq!(error("credits in 20..x"), (15, "x".to_owned()));
+        assert_eq!(error("credits in 30..20"), (15, "20".to_owned()));
+        assert_eq!(error("free(funday)"), (5, "funday".to_owned()));
+        assert_eq!(error("start >= 25:00"), (9, "25:00".to_owned()));
+        assert_eq!(
+            error("subjects in 1..2 credits"),
+            (17, "credits".to_owned())
+        );
+        assert_eq!(error("seats > 2"), (6, ">".to_owned()));
+        assert_eq!(error("(seats >= 2"), (11, "".to_owned()));
+        assert_eq!(
+            parse_filter("free(monday").err().unwrap().to_string(),
+            "Expected `)` at the end of the expression"
+        );
+    }
+}
@@ -1,28 +1,216 @@
+use enum_map::EnumMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
 use std::ops::RangeBounds;
+use std::rc::Rc;
+use strum::IntoEnumIterator;
 
-use crate::models::SubjectCommision;
+use crate::models::{
+    Building, Code, Combinable, Day, DaysOfTheWeek, Span, SubjectCommision, TaskInfo, Time, Week,
+};
 
-type Choice = Vec<Option<SubjectCommision>>;
+type Choice<T> = Vec<Option<T>>;
 
-pub trait ChoiceFilter {
-    fn filter(&self, item: &Choice) -> bool;
+/// Items that are worth credits.
+pub trait Credited {
+    fn credits(&self) -> u32;
 }
 
-pub struct ChoiceFilterIterator<I: Iterator<Item = Choice>, F: ChoiceFilter> {
+/// Items that belong to a subject.
+pub trait OfSubject {
+    fn subject_code(&self) -> Code;
+}
+
+/// Items that take place at fixed times during the week.
+pub trait Scheduled {
+    type Info;
+    fn schedule(&self) -> &Week<Self::Info>;
+}
+
+impl Credited for SubjectCommision {
+    fn credits(&self) -> u32 {
+        self.subject.upgrade().unwrap().borrow().credits as u32
+    }
+}
+
+impl OfSubject for SubjectCommision {
+    fn subject_code(&self) -> Code {
+        self.subject.upgrade().unwrap().borrow().code
+    }
+}
+
+impl Scheduled for SubjectCommision {
+    type Info = TaskInfo;
+    fn schedule(&self) -> &Week<Self::Info> {
+        &self.schedule
+    }
+}
+
+impl<T> Scheduled for Week<T> {
+    type Info = T;
+    fn schedule(&self) -> &Week<T> {
+        self
+    }
+}
+
+/// Items with a limited number of seats.
+pub trait Seated {
+    /// `None` when the number of seats is not known.
+    fn free_seats(&self) -> Option<u64>;
+}
+
+impl Seated for SubjectCommision {
+    fn free_seats(&self) -> Option<u64> {
+        self.free_seats
+    }
+}
+
+/// Class details that say where the class takes place.
+pub trait Located {
+    fn buildings(&self) -> &HashSet<Building>;
+}
+
+impl Located for TaskInfo {
+    fn buildings(&self) -> &HashSet<Building> {
+        &self.buildings
+    }
+}
+
+pub trait ChoiceFilter<T> {
+    fn filter(&self, item: &Choice<T>) -> bool;
+}
+
+impl<T, F: Fn(&Choice<T>) -> bool> ChoiceFilter<T> for F {
+    fn filter(&self, item: &Choice<T>) -> bool {
+        self(item)
+    }
+}
+
+/// Accepts options that pass both filters.
+pub struct And<A, B>(pub A, pub B);
+
+impl<T, A: ChoiceFilter<T>, B: ChoiceFilter<T>> ChoiceFilter<T> for And<A, B> {
+    fn filter(&self, item: &Choice<T>) -> bool {
+        self.0.filter(item) && self.1.filter(item)
+    }
+}
+
+/// Accepts options that pass any of the two filters.
+pub struct Or<A, B>(pub A, pub B);
+
+impl<T, A: ChoiceFilter<T>, B: ChoiceFilter<T>> ChoiceFilter<T> for Or<A, B> {
+    fn filter(&self, item: &Choice<T>) -> bool {
+        self.0.filter(item) || self.1.filter(item)
+    }
+}
+
+/// Accepts options that the filter rejects.
+pub struct Not<A>(pub A);
+
+impl<T, A: ChoiceFilter<T>> ChoiceFilter<T> for Not<A> {
+    fn filter(&self, item: &Choice<T>) -> bool {
+        !self.0.filter(item)
+    }
+}
+
+/// A filter whose type is only known at runtime, e.g. built from configuration.
+pub struct BoxedFilter<'a, T>(Box<dyn ChoiceFilter<T> + 'a>);
+
+impl<'a, T> BoxedFilter<'a, T> {
+    pub fn new(filter: impl ChoiceFilter<T> + 'a) -> Self {
+        Self(Box::new(filter))
+    }
+}
+
+impl<T> ChoiceFilter<T> for BoxedFilter<'_, T> {
+    fn filter(&self, item: &Choice<T>) -> bool {
+        self.0.filter(item)
+    }
+}
+
+/// How many options a tracked filter looked at and how many of them it rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterCount {
+    pub name: String,
+    pub seen: u64,
+    pub rejected: u64,
+}
+
+/// Counts shared by every filter wrapped with [`track`](Self::track), to find out which filter
+/// left an option list empty.
+#[derive(Debug, Clone, Default)]
+pub struct FilterStatistics(Rc<RefCell<Vec<FilterCount>>>);
+
+impl FilterStatistics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn track<F>(&self, name: impl Into<String>, filter: F) -> Tracked<F> {
+        let mut counts = self.0.borrow_mut();
+        counts.push(FilterCount {
+            name: name.into(),
+            seen: 0,
+            rejected: 0,
+        });
+        Tracked {
+            filter,
+            index: counts.len() - 1,
+            statistics: self.clone(),
+        }
+    }
+
+    /// Counts in the order the filters were tracked.
+    pub fn counts(&self) -> Vec<FilterCount> {
+        self.0.borrow().clone()
+    }
+}
+
+impl Display for FilterStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for count in self.0.borrow().iter() {
+            writeln!(
+                f,
+                "{}: rejected {} of {}",
+                count.name, count.rejected, count.seen
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Tracked<F> {
+    filter: F,
+    index: usize,
+    statistics: FilterStatistics,
+}
+
+impl<T, F: ChoiceFilter<T>> ChoiceFilter<T> for Tracked<F> {
+    fn filter(&self, item: &Choice<T>) -> bool {
+        let accepted = self.filter.filter(item);
+        let mut counts = self.statistics.0.borrow_mut();
+        counts[self.index].seen += 1;
+        counts[self.index].rejected += u64::from(!accepted);
+        accepted
+    }
+}
+
+pub struct ChoiceFilterIterator<I, F> {
     iterator: I,
     filter: F,
 }
 
-impl<I: Iterator<Item = Choice>, F: ChoiceFilter> Iterator for ChoiceFilterIterator<I, F> {
-    type Item = Choice;
+impl<T, I: Iterator<Item = Choice<T>>, F: ChoiceFilter<T>> Iterator for ChoiceFilterIterator<I, F> {
+    type Item = Choice<T>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.iterator.find(|c| self.filter.filter(c))
     }
 }
 
-pub trait ChoiceIterator: Iterator<Item = Choice> {
-    fn filter_choices<F: ChoiceFilter>(self, filter: F) -> ChoiceFilterIterator<Self, F>
+pub trait ChoiceIterator<T>: Iterator<Item = Choice<T>> {
+    fn filter_choices<F: ChoiceFilter<T>>(self, filter: F) -> ChoiceFilterIterator<Self, F>
     where
         Self: Sized,
     {
@@ -33,7 +221,7 @@ pub trait ChoiceIterator: Iterator<Item = Choice> {
     }
 }
 
-impl<I: Iterator<Item = Choice>> ChoiceIterator for I {}
+impl<T, I: Iterator<Item = Choice<T>>> ChoiceIterator<T> for I {}
 
 pub struct CreditCount<R: RangeBounds<u32>> {
     valid_range: R,
@@ -45,13 +233,9 @@ impl<R: RangeBounds<u32>> CreditCount<R> {
     }
 }
 
-impl<R: RangeBounds<u32>> ChoiceFilter for CreditCount<R> {
-    fn filter(&self, item: &Choice) -> bool {
-        let credits = item
-            .iter()
-            .flatten()
-            .map(|c| c.subject.upgrade().unwrap().borrow().credits as u32)
-            .sum();
+impl<T: Credited, R: RangeBounds<u32>> ChoiceFilter<T> for CreditCount<R> {
+    fn filter(&self, item: &Choice<T>) -> bool {
+        let credits = item.iter().flatten().map(Credited::credits).sum();
         self.valid_range.contains(&credits)
     }
 }
@@ -66,9 +250,711 @@ impl<R: RangeBounds<u32>> SubjectCount<R> {
     }
 }
 
-impl<R: RangeBounds<u32>> ChoiceFilter for SubjectCount<R> {
-    fn filter(&self, item: &Choice) -> bool {
+impl<T, R: RangeBounds<u32>> ChoiceFilter<T> for SubjectCount<R> {
+    fn filter(&self, item: &Choice<T>) -> bool {
         let credits = item.iter().flatten().count() as u32;
         self.valid_range.contains(&credits)
     }
 }
+
+/// The week that all items of an option add up to.
+pub fn combined_schedule<T>(item: &Choice<T>) -> Week<T::Info>
+where
+    T: Scheduled,
+    T::Info: Clone,
+{
+    item.iter()
+        .flatten()
+        .fold(Week::empty(), |week, c| week.combine(c.schedule()))
+}
+
+/// Accepts options that leave at least a number of days, and/or some given days, without
+/// classes. As a soft constraint it makes a good "prefer free Fridays".
+///
+/// Only days that can have classes count towards the number, Monday to Saturday unless set
+/// with [`set_class_days`](Self::set_class_days).
+#[derive(Debug, Clone)]
+pub struct FreeDays {
+    at_least: usize,
+    days: Vec<DaysOfTheWeek>,
+    class_days: Vec<DaysOfTheWeek>,
+}
+
+impl Default for FreeDays {
+    fn default() -> Self {
+        Self {
+            at_least: 0,
+            days: vec![],
+            class_days: DaysOfTheWeek::iter()
+                .filter(|day| *day != DaysOfTheWeek::Sunday)
+                .collect(),
+        }
+    }
+}
+
+impl FreeDays {
+    pub fn at_least(count: usize) -> Self {
+        Self {
+            at_least: count,
+            ..Self::default()
+        }
+    }
+
+    pub fn on(days: impl IntoIterator<Item = DaysOfTheWeek>) -> Self {
+        Self {
+            days: days.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn set_class_days(&mut self, days: impl IntoIterator<Item = DaysOfTheWeek>) -> &mut Self {
+        self.class_days = days.into_iter().collect();
+        self
+    }
+}
+
+impl<T> ChoiceFilter<T> for FreeDays
+where
+    T: Scheduled,
+    T::Info: Clone,
+{
+    fn filter(&self, item: &Choice<T>) -> bool {
+        let week = combined_schedule(item);
+        let free = |day: &DaysOfTheWeek| week.days[*day].tasks.is_empty();
+        self.days.iter().all(free)
+            && self.class_days.iter().filter(|day| free(day)).count() >= self.at_least
+    }
+}
+
+/// Accepts options whose classes all start at or after the given time.
+pub struct NotBefore(pub Time);
+
+impl<T: Scheduled> ChoiceFilter<T> for NotBefore {
+    fn filter(&self, item: &Choice<T>) -> bool {
+        item.iter()
+            .flatten()
+            .flat_map(|c| c.schedule().days.values())
+            .flat_map(|day| &day.tasks)
+            .all(|task| task.span.start >= self.0)
+    }
+}
+
+/// Accepts options whose classes all fall within the allowed window of their day. Days
+/// without a window are not restricted.
+#[derive(Debug, Clone, Default)]
+pub struct TimeWindow {
+    windows: EnumMap<DaysOfTheWeek, Option<Span>>,
+}
+
+impl TimeWindow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_day(&mut self, day: DaysOfTheWeek, window: Span) -> &mut Self {
+        self.windows[day] = Some(window);
+        self
+    }
+
+    pub fn set_days(
+        &mut self,
+        days: impl IntoIterator<Item = DaysOfTheWeek>,
+        window: Span,
+    ) -> &mut Self {
+        for day in days {
+            self.set_day(day, window);
+        }
+        self
+    }
+
+    /// Whether all classes of a single item fall within the windows.
+    pub fn allows<T: Scheduled>(&self, item: &T) -> bool {
+        item.schedule().days.iter().all(|(day, classes)| {
+            self.windows[day].is_none_or(|window| {
+                classes
+                    .tasks
+                    .iter()
+                    .all(|task| window.start <= task.span.start && task.span.end <= window.end)
+            })
+        })
+    }
+}
+
+impl<T: Scheduled> ChoiceFilter<T> for TimeWindow {
+    fn filter(&self, item: &Choice<T>) -> bool {
+        item.iter().flatten().all(|c| self.allows(c))
+    }
+}
+
+/// The stretches of a day with back-to-back classes.
+fn blocks<T>(day: &Day<T>) -> Vec<Span> {
+    let mut blocks: Vec<Span> = vec![];
+    for task in &day.tasks {
+        match blocks.last_mut() {
+            Some(block) if task.span.start <= block.end => block.end = block.end.max(task.span.end),
+            _ => blocks.push(task.span),
+        }
+    }
+    blocks
+}
+
+/// Longest stretch without classes inside `window`.
+fn longest_break(blocks: &[Span], window: Span) -> u64 {
+    let mut longest = 0;
+    let mut free_from = window.start;
+    for block in blocks {
+        if block.start > free_from {
+            longest = longest.max(block.start.min(window.end) - free_from);
+        }
+        free_from = free_from.max(block.end);
+        if free_from >= window.end {
+            return longest;
+        }
+    }
+    longest.max(window.end - free_from)
+}
+
+/// Accepts options that keep every day bearable: limited class minutes, limited back-to-back
+/// classes and a break inside a window, e.g. for lunch, on days with classes around it.
+#[derive(Debug, Clone, Default)]
+pub struct DailyLoad {
+    max_minutes: Option<u64>,
+    max_block: Option<u64>,
+    min_break: Option<(Span, u64)>,
+}
+
+impl DailyLoad {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_max_minutes(&mut self, minutes: u64) -> &mut Self {
+        self.max_minutes = Some(minutes);
+        self
+    }
+
+    pub fn set_max_block(&mut self, minutes: u64) -> &mut Self {
+        self.max_block = Some(minutes);
+        self
+    }
+
+    pub fn set_min_break(&mut self, window: Span, minutes: u64) -> &mut Self {
+        self.min_break = Some((window, minutes));
+        self
+    }
+
+    fn allows(&self, blocks: &[Span]) -> bool {
+        let (Some(first), Some(last)) = (blocks.first(), blocks.last()) else {
+            return true;
+        };
+        let minutes = blocks.iter().map(Span::duration).sum::<u64>();
+        self.max_minutes.is_none_or(|max| minutes <= max)
+            && self
+                .max_block
+                .is_none_or(|max| blocks.iter().all(|block| block.duration() <= max))
+            && self.min_break.is_none_or(|(window, minutes)| {
+                first.start >= window.end
+                    || last.end <= window.start
+                    || longest_break(blocks, window) >= minutes
+            })
+    }
+}
+
+impl<T> ChoiceFilter<T> for DailyLoad
+where
+    T: Scheduled,
+    T::Info: Clone,
+{
+    fn filter(&self, item: &Choice<T>) -> bool {
+        combined_schedule(item)
+            .days
+            .values()
+            .all(|day| self.allows(&blocks(day)))
+    }
+}
+
+/// Accepts options without large holes between classes of the same day.
+#[derive(Debug, Clone, Default)]
+pub struct IdleGaps {
+    max_gap: Option<u64>,
+    max_per_day: Option<u64>,
+    max_per_week: Option<u64>,
+}
+
+impl IdleGaps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_max_gap(&mut self, minutes: u64) -> &mut Self {
+        self.max_gap = Some(minutes);
+        self
+    }
+
+    pub fn set_max_per_day(&mut self, minutes: u64) -> &mut Self {
+        self.max_per_day = Some(minutes);
+        self
+    }
+
+    pub fn set_max_per_week(&mut self, minutes: u64) -> &mut Self {
+        self.max_per_week = Some(minutes);
+        self
+    }
+}
+
+impl<T> ChoiceFilter<T> for IdleGaps
+where
+    T: Scheduled,
+    T::Info: Clone,
+{
+    fn filter(&self, item: &Choice<T>) -> bool {
+        let mut week_idle = 0;
+        for day in combined_schedule(item).days.values() {
+            let gaps = blocks(day)
+                .windows(2)
+                .map(|pair| pair[1].start - pair[0].end)
+                .collect::<Vec<_>>();
+            let day_idle = gaps.iter().sum::<u64>();
+            if self
+                .max_gap
+                .is_some_and(|max| gaps.iter().any(|gap| *gap > max))
+                || self.max_per_day.is_some_and(|max| day_idle > max)
+            {
+                return false;
+            }
+            week_idle += day_idle;
+        }
+        self.max_per_week.is_none_or(|max| week_idle <= max)
+    }
+}
+
+/// Accepts options that respect where classes take place: avoided buildings, how many
+/// buildings a day takes, the time to walk between them and how many days a building (e.g. a
+/// remote one) is visited. Classes without a known building are not restricted.
+#[derive(Debug, Clone, Default)]
+pub struct Buildings {
+    avoid: HashSet<Building>,
+    max_per_day: Option<usize>,
+    min_change_gap: Option<u64>,
+    max_days: HashMap<Building, usize>,
+}
+
+impl Buildings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_avoid(&mut self, buildings: impl IntoIterator<Item = Building>) -> &mut Self {
+        self.avoid = buildings.into_iter().collect();
+        self
+    }
+
+    pub fn set_max_per_day(&mut self, buildings: usize) -> &mut Self {
+        self.max_per_day = Some(buildings);
+        self
+    }
+
+    /// Consecutive classes in different buildings need at least `minutes` between them.
+    pub fn set_min_change_gap(&mut self, minutes: u64) -> &mut Self {
+        self.min_change_gap = Some(minutes);
+        self
+    }
+
+    pub fn set_max_days_in(&mut self, building: Building, days: usize) -> &mut Self {
+        self.max_days.insert(building, days);
+        self
+    }
+
+    fn allows<T: Located>(&self, day: &Day<T>, days_in: &mut HashMap<Building, usize>) -> bool {
+        let visited = day
+            .tasks
+            .iter()
+            .flat_map(|task| task.info.buildings())
+            .collect::<HashSet<_>>();
+        if visited.iter().any(|building| self.avoid.contains(building))
+            || self.max_per_day.is_some_and(|max| visited.len() > max)
+        {
+            return false;
+        }
+        for building in visited {
+            *days_in.entry(building.clone()).or_default() += 1;
+        }
+        self.min_change_gap.is_none_or(|min| {
+            day.tasks.windows(2).all(|pair| {
+                let (from, to) = (pair[0].info.buildings(), pair[1].info.buildings());
+                let gap = match pair[1].span.start > pair[0].span.end {
+                    true => pair[1].span.start - pair[0].span.end,
+                    false => 0,
+                };
+                from.is_empty() || to.is_empty() || !from.is_disjoint(to) || gap >= min
+            })
+        })
+    }
+}
+
+impl<T> ChoiceFilter<T> for Buildings
+where
+    T: Scheduled,
+    T::Info: Located + Clone,
+{
+    fn filter(&self, item: &Choice<T>) -> bool {
+        let mut days_in = HashMap::new();
+        combined_schedule(item)
+            .days
+            .values()
+            .all(|day| self.allows(day, &mut days_in))
+            && self
+                .max_days
+                .iter()
+                .all(|(building, max)| days_in.get(building).is_none_or(|days| days <= max))
+    }
+}
+
+/// Accepts options where every chosen item has at least the given number of free seats, so
+/// `FreeSeats(1)` drops full commissions. Items with unknown seats are accepted.
+#[derive(Debug, Clone, Copy)]
+pub struct FreeSeats(pub u64);
+
+impl FreeSeats {
+    /// Whether a single item has enough seats, e.g. to prune commissions before searching.
+    pub fn allows<T: Seated>(&self, item: &T) -> bool {
+        item.free_seats().is_none_or(|seats| seats >= self.0)
+    }
+}
+
+impl<T: Seated> ChoiceFilter<T> for FreeSeats {
+    fn filter(&self, item: &Choice<T>) -> bool {
+        item.iter().flatten().all(|c| self.allows(c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{class, entry, label, SubjectBuilder};
+    use crate::models::Task;
+    use crate::option_generator::OptionGenerator;
+    use crate::t;
+    use std::sync::Weak;
+
+    #[test]
+    fn filter_by_subject() {
+        fn without<T: OfSubject>(code: Code) -> impl Fn(&Choice<T>) -> bool {
+            move |item| item.iter().flatten().all(|c| c.subject_code() != code)
+        }
+
+        let subjects = [
+            SubjectBuilder::new("01.01")
+                .commission("A", &[class(DaysOfTheWeek::Monday, "08:00", "10:00")])
+                .build(),
+            SubjectBuilder::new("01.02")
+                .commission("B", &[class(DaysOfTheWeek::Tuesday, "08:00", "10:00")])
+                .build(),
+        ];
+        let mut generator = OptionGenerator::default();
+        generator.set_optional(subjects.iter().map(entry).collect());
+        assert_eq!(
+            generator
+                .generate()
+                .filter_choices(without(t!("01.02")))
+                .map(|option| label(&option))
+                .collect::<Vec<_>>(),
+            vec!["A-", "--"]
+        );
+    }
+
+    #[test]
+    fn filter_bare_items() {
+        let sa = Span::new(t!("08:00"), t!("10:00"));
+        let sb = Span::new(t!("10:00"), t!("12:00"));
+        let mut generator = OptionGenerator::default();
+        generator.set_optional(vec![("0", vec![sa]), ("1", vec![sa, sb])]);
+        assert_eq!(
+            generator
+                .generate()
+                .filter_choices(SubjectCount::new(2..))
+                .collect::<Vec<_>>(),
+            vec![vec![Some(sa), Some(sb)]]
+        );
+
+        let week = |day: DaysOfTheWeek, span: Span| {
+            let mut week = Week::empty();
+            week.days[day] = Day::new(vec![Task::new(span, ())]);
+            week
+        };
+        let monday = week(DaysOfTheWeek::Monday, sa);
+        let friday = week(DaysOfTheWeek::Friday, sb);
+        let mut generator = OptionGenerator::default();
+        generator.set_mandatory(vec![("0", vec![monday.clone(), friday.clone()])]);
+        assert_eq!(
+            generator
+                .generate()
+                .filter_choices(FreeDays::on([DaysOfTheWeek::Friday]))
+                .filter_choices(NotBefore(t!("08:00")))
+                .collect::<Vec<_>>(),
+            vec![vec![Some(monday)]]
+        );
+    }
+
+    #[test]
+    fn combinators() {
+        let sa = Span::new(t!("08:00"), t!("10:00"));
+        let sb = Span::new(t!("10:00"), t!("12:00"));
+        let sc = Span::new(t!("12:00"), t!("14:00"));
+        let mut generator = OptionGenerator::default();
+        generator.set_optional(vec![("0", vec![sa, sb]), ("1", vec![sc])]);
+        let starts_at_eight = |item: &Vec<Option<Span>>| item[0] == Some(sa);
+        let filters = vec![
+            BoxedFilter::new(Or(starts_at_eight, SubjectCount::new(..1))),
+            BoxedFilter::new(Not(And(
+                SubjectCount::new(1..=1),
+                |item: &Vec<Option<Span>>| item[1].is_some(),
+            ))),
+        ];
+        let options = filters
+            .into_iter()
+            .fold(
+                Box::new(generator.generate()) as Box<dyn Iterator<Item = _>>,
+                |options, filter| Box::new(options.filter_choices(filter)),
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(
+            options,
+            vec![
+                vec![Some(sa), Some(sc)],
+                vec![Some(sa), None],
+                vec![None, None]
+            ]
+        );
+    }
+
+    #[test]
+    fn time_window() {
+        let week = |day: DaysOfTheWeek, start: &str, end: &str| {
+            let mut week = Week::empty();
+            week.days[day] = Day::new(vec![Task::new(Span::new(t!(start), t!(end)), ())]);
+            week
+        };
+        let morning = week(DaysOfTheWeek::Monday, "08:00", "10:00");
+        let evening = week(DaysOfTheWeek::Monday, "18:00", "20:00");
+        let saturday = week(DaysOfTheWeek::Saturday, "09:00", "12:00");
+        let late_saturday = week(DaysOfTheWeek::Saturday, "12:00", "14:00");
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![("0", vec![morning, evening.clone()])])
+            .set_optional(vec![("1", vec![saturday.clone(), late_saturday])]);
+
+        let mut window = TimeWindow::new();
+        window
+            .set_days(
+                [DaysOfTheWeek::Monday, DaysOfTheWeek::Tuesday],
+                Span::new(t!("18:00"), t!("24:00")),
+            )
+            .set_day(DaysOfTheWeek::Saturday, Span::new(t!("00:00"), t!("13:00")));
+        let expected = vec![
+            vec![Some(evening.clone()), Some(saturday.clone())],
+            vec![Some(evening), None],
+        ];
+        assert_eq!(
+            generator
+                .clone()
+                .generate()
+                .filter_choices(window.clone())
+                .collect::<Vec<_>>(),
+            expected
+        );
+        generator.retain_items(|item| window.allows(item));
+        assert_eq!(generator.generate().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn free_days() {
+        let week = |days: &[DaysOfTheWeek]| {
+            let mut week = Week::empty();
+            for day in days {
+                week.days[*day] =
+                    Day::new(vec![Task::new(Span::new(t!("08:00"), t!("10:00")), ())]);
+            }
+            week
+        };
+        let options = [
+            vec![Some(week(&[DaysOfTheWeek::Monday, DaysOfTheWeek::Friday]))],
+            vec![Some(week(
+                &DaysOfTheWeek::iter().skip(1).take(5).collect::<Vec<_>>(),
+            ))],
+            vec![Some(week(&[DaysOfTheWeek::Monday])), None],
+        ];
+        let accepted = |filter: FreeDays| {
+            options
+                .iter()
+                .map(|option| filter.filter(option))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(accepted(FreeDays::at_least(5)), vec![false, false, true]);
+        assert_eq!(accepted(FreeDays::at_least(3)), vec![true, false, true]);
+        assert_eq!(accepted(FreeDays::at_least(6)), vec![false, false, false]);
+        assert_eq!(
+            accepted(
+                FreeDays::at_least(4)
+                    .set_class_days(DaysOfTheWeek::iter().skip(1).take(5))
+                    .clone()
+            ),
+            vec![false, false, true]
+        );
+        assert_eq!(
+            accepted(FreeDays::on([DaysOfTheWeek::Friday])),
+            vec![false, false, true]
+        );
+    }
+
+    #[test]
+    fn daily_load() {
+        let day = |spans: &[(&str, &str)]| {
+            let mut week = Week::empty();
+            week.days[DaysOfTheWeek::Monday] = Day::new(
+                spans
+                    .iter()
+                    .map(|(start, end)| Task::new(Span::new(t!(start), t!(end)), ()))
+                    .collect(),
+            );
+            vec![Some(week)]
+        };
+        let long_morning = day(&[("08:00", "10:00"), ("10:00", "12:00"), ("09:00", "11:00")]);
+        let short_lunch = day(&[("10:00", "12:30"), ("13:00", "15:00")]);
+        let late_lunch = day(&[("12:00", "13:30"), ("14:30", "16:00")]);
+        let afternoon = day(&[("14:30", "18:00")]);
+
+        let mut load = DailyLoad::new();
+        load.set_max_minutes(240);
+        assert!(load.filter(&long_morning));
+        assert!(!load.filter(&short_lunch));
+
+        let mut load = DailyLoad::new();
+        load.set_max_block(180);
+        assert!(!load.filter(&long_morning));
+        assert!(load.filter(&short_lunch));
+        assert!(!load.filter(&afternoon));
+
+        let mut load = DailyLoad::new();
+        load.set_min_break(Span::new(t!("12:00"), t!("14:30")), 45);
+        assert!(load.filter(&long_morning));
+        assert!(!load.filter(&short_lunch));
+        assert!(load.filter(&late_lunch));
+        assert!(load.filter(&afternoon));
+    }
+
+    #[test]
+    fn idle_gaps() {
+        let mut week = Week::empty();
+        week.days[DaysOfTheWeek::Monday] = Day::new(vec![
+            Task::new(Span::new(t!("08:00"), t!("10:00")), ()),
+            Task::new(Span::new(t!("11:00"), t!("12:00")), ()),
+            Task::new(Span::new(t!("13:30"), t!("15:00")), ()),
+        ]);
+        week.days[DaysOfTheWeek::Tuesday] = Day::new(vec![
+            Task::new(Span::new(t!("08:00"), t!("10:00")), ()),
+            Task::new(Span::new(t!("09:00"), t!("11:00")), ()),
+            Task::new(Span::new(t!("11:30"), t!("12:00")), ()),
+        ]);
+        let option = vec![Some(week)];
+
+        let accepts = |configure: fn(&mut IdleGaps) -> &mut IdleGaps| {
+            let mut gaps = IdleGaps::new();
+            configure(&mut gaps);
+            gaps.filter(&option)
+        };
+        assert!(accepts(|gaps| gaps.set_max_gap(90)));
+        assert!(!accepts(|gaps| gaps.set_max_gap(60)));
+        assert!(accepts(|gaps| gaps.set_max_per_day(150)));
+        assert!(!accepts(|gaps| gaps.set_max_per_day(120)));
+        assert!(accepts(|gaps| gaps.set_max_per_week(180)));
+        assert!(!accepts(|gaps| gaps.set_max_per_week(179)));
+    }
+
+    #[test]
+    fn buildings() {
+        let class = |start: &str, end: &str, building: &str| {
+            Task::new(
+                Span::new(t!(start), t!(end)),
+                TaskInfo {
+                    subject: Weak::new(),
+                    buildings: HashSet::from([Building {
+                        name: building.to_owned(),
+                    }]),
+                },
+            )
+        };
+        let building = |name: &str| Building {
+            name: name.to_owned(),
+        };
+        let mut week = Week::empty();
+        week.days[DaysOfTheWeek::Monday] = Day::new(vec![
+            class("08:00", "10:00", "A"),
+            class("10:15", "12:00", "B"),
+            class("12:00", "13:00", "B"),
+        ]);
+        week.days[DaysOfTheWeek::Thursday] = Day::new(vec![class("18:00", "20:00", "External")]);
+        let mut external = Week::empty();
+        external.days[DaysOfTheWeek::Friday] = Day::new(vec![class("18:00", "20:00", "External")]);
+        let option = vec![Some(week), None];
+        let spread = vec![option[0].clone(), Some(external)];
+
+        assert!(Buildings::new().set_avoid([building("C")]).filter(&option));
+        assert!(!Buildings::new().set_avoid([building("A")]).filter(&option));
+        assert!(Buildings::new().set_max_per_day(2).filter(&option));
+        assert!(!Buildings::new().set_max_per_day(1).filter(&option));
+        assert!(Buildings::new().set_min_change_gap(15).filter(&option));
+        assert!(!Buildings::new().set_min_change_gap(20).filter(&option));
+        let mut one_external_day = Buildings::new();
+        one_external_day.set_max_days_in(building("External"), 1);
+        assert!(one_external_day.filter(&option));
+        assert!(!one_external_day.filter(&spread));
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    struct Seats(Option<u64>);
+
+    impl Seated for Seats {
+        fn free_seats(&self) -> Option<u64> {
+            self.0
+        }
+    }
+
+    #[test]
+    fn free_seats() {
+        let full = Seats(Some(0));
+        let last_seat = Seats(Some(1));
+        let roomy = Seats(Some(10));
+        let unknown = Seats(None);
+
+        assert!(FreeSeats(1).filter(&vec![Some(last_seat), Some(unknown), None]));
+        assert!(!FreeSeats(1).filter(&vec![Some(full), Some(roomy)]));
+        assert!(!FreeSeats(5).filter(&vec![Some(last_seat), Some(roomy)]));
+        assert!(!FreeSeats(5).allows(&last_seat));
+        assert!(FreeSeats(5).allows(&roomy));
+        assert!(FreeSeats(5).allows(&unknown));
+    }
+
+    #[test]
+    fn filter_statistics() {
+        let sa = Span::new(t!("08:00"), t!("10:00"));
+        let sb = Span::new(t!("10:00"), t!("12:00"));
+        let mut generator = OptionGenerator::default();
+        generator.set_optional(vec![("0", vec![sa]), ("1", vec![sa, sb])]);
+        let statistics = FilterStatistics::new();
+        let options = generator
+            .generate()
+            .filter_choices(statistics.track("subjects", SubjectCount::new(1..)))
+            .filter_choices(
+                statistics.track("starts at eight", |item: &Vec<Option<Span>>| {
+                    item.iter().flatten().any(|span| *span == sa)
+                }),
+            )
+            .count();
+        assert_eq!(options, 3);
+        assert_eq!(
+            statistics.to_string(),
+            "subjects: rejected 1 of 5\nstarts at eight: rejected 1 of 4\n"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use super::Chosen;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Stops a generation from another place, e.g. another thread or a UI handler.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Cancelled,
+    Deadline,
+    NodeBudget,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Progress {
+    /// Partial choices looked at, including the ones that were pruned.
+    pub nodes_visited: u64,
+    pub options_found: u64,
+    /// Estimated fraction of the search space already walked, between 0 and 1.
+    pub covered: f64,
+    /// Why the generation ended early, if it did.
+    pub stopped: Option<StopReason>,
+}
+
+type ProgressCallback = Box<dyn FnMut(&Progress)>;
+
+/// Limits and reporting for [`generate_with_context`](super::OptionGenerator::generate_with_context).
+#[derive(Default)]
+pub struct GenerationContext {
+    token: Option<CancellationToken>,
+    deadline: Option<Instant>,
+    node_budget: Option<u64>,
+    report_every: u64,
+    on_progress: Option<ProgressCallback>,
+}
+
+impl GenerationContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) -> &mut Self {
+        self.token = Some(token);
+        self
+    }
+
+    pub fn set_deadline(&mut self, deadline: Instant) -> &mut Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn set_time_budget(&mut self, budget: Duration) -> &mut Self {
+        self.set_deadline(Instant::now() + budget)
+    }
+
+    pub fn set_node_budget(&mut self, nodes: u64) -> &mut Self {
+        self.node_budget = Some(nodes);
+        self
+    }
+
+    /// `callback` is called every `every` visited nodes and once more when generation ends.
+    pub fn set_progress_callback(
+        &mut self,
+        every: u64,
+        callback: impl FnMut(&Progress) + 'static,
+    ) -> &mut Self {
+        self.report_every = every;
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    fn stop_reason(&self, nodes_visited: u64) -> Option<StopReason> {
+        if self
+            .token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            Some(StopReason::Cancelled)
+        } else if self
+            .node_budget
+            .is_some_and(|budget| nodes_visited >= budget)
+        {
+            Some(StopReason::NodeBudget)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Some(StopReason::Deadline)
+        } else {
+            None
+        }
+    }
+}
+
+pub(super) struct Monitor {
+    context: GenerationContext,
+    /// Number of branches of every group, used to estimate the covered fraction.
+    branches: Vec<usize>,
+    progress: Progress,
+    finished: bool,
+}
+
+impl Monitor {
+    pub(super) fn new(context: GenerationContext, branches: Vec<usize>) -> Self {
+        Self {
+            context,
+            branches,
+            progress: Progress::default(),
+            finished: false,
+        }
+    }
+
+    fn report(&mut self) {
+        if let Some(on_progress) = &mut self.context.on_progress {
+            on_progress(&self.progress);
+        }
+    }
+
+    /// Where the subtree of `chosen` starts, as a fraction of the whole search space.
+    fn covered<K, T>(&self, chosen: &Chosen<K, T>) -> f64 {
+        let mut covered = 0.0;
+        let mut width = 1.0;
+        for ((_, index, _), branches) in chosen.iter().zip(&self.branches) {
+            width /= *branches as f64;
+            covered += *index as f64 * width;
+        }
+        covered
+    }
+
+    /// Whether the search should go on into `chosen`.
+    pub(super) fn visit<K, T>(&mut self, chosen: &Chosen<K, T>) -> bool {
+        if self.progress.stopped.is_some() {
+            return false;
+        }
+        if let Some(reason) = self.context.stop_reason(self.progress.nodes_visited) {
+            self.progress.stopped = Some(reason);
+            self.progress.covered = self.covered(chosen);
+            self.finished = true;
+            self.report();
+            return false;
+        }
+        self.progress.nodes_visited += 1;
+        let every = self.context.report_every;
+        if every > 0 && self.progress.nodes_visited.is_multiple_of(every) {
+            self.progress.covered = self.covered(chosen);
+            self.report();
+        }
+        true
+    }
+
+    pub(super) fn found(&mut self) {
+        self.progress.options_found += 1;
+    }
+
+    pub(super) fn finish(&mut self) {
+        if !self.finished {
+            self.finished = true;
+            self.progress.covered = 1.0;
+            self.report();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Span;
+    use crate::option_generator::OptionGenerator;
+    use crate::t;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn generate(context: GenerationContext) -> Vec<Vec<Option<Span>>> {
+        let sa = Span::new(t!("00:00"), t!("01:00"));
+        let sb = Span::new(t!("01:00"), t!("02:00"));
+        let sc = Span::new(t!("02:00"), t!("03:00"));
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![("0", vec![sa, sc])])
+            .set_optional(vec![("1", vec![sb])]);
+        generator.generate_with_context(context).collect()
+    }
+
+    fn reporting(context: &mut GenerationContext) -> Rc<RefCell<Vec<Progress>>> {
+        let reports = Rc::new(RefCell::new(vec![]));
+        context.set_progress_callback(1, {
+            let reports = reports.clone();
+            move |progress| reports.borrow_mut().push(*progress)
+        });
+        reports
+    }
+
+    #[test]
+    fn progress_until_the_end() {
+        let mut context = GenerationContext::new();
+        let reports = reporting(&mut context);
+        assert_eq!(generate(context).len(), 4);
+        let reports = reports.borrow();
+        assert_eq!(reports.len(), 7);
+        assert_eq!(
+            reports.last(),
+            Some(&Progress {
+                nodes_visited: 6,
+                options_found: 4,
+                covered: 1.0,
+                stopped: None,
+            })
+        );
+    }
+
+    #[test]
+    fn node_budget() {
+        let mut context = GenerationContext::new();
+        context.set_node_budget(4);
+        let reports = reporting(&mut context);
+        assert_eq!(generate(context).len(), 2);
+        assert_eq!(
+            reports.borrow().last(),
+            Some(&Progress {
+                nodes_visited: 4,
+                options_found: 2,
+                covered: 0.5,
+                stopped: Some(StopReason::NodeBudget),
+            })
+        );
+    }
+
+    #[test]
+    fn cancellation() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut context = GenerationContext::new();
+        context.set_cancellation_token(token);
+        let reports = reporting(&mut context);
+        assert!(generate(context).is_empty());
+        assert_eq!(reports.borrow().len(), 1);
+        assert_eq!(reports.borrow()[0].stopped, Some(StopReason::Cancelled));
+    }
+}
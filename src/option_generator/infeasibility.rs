@@ -0,0 +1,31 @@
+use crate::models::SubjectCommision;
+use std::fmt::Display;
+
+/// A minimal set of mandatory subjects that cannot be scheduled together: dropping any one of
+/// them makes the rest schedulable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Infeasibility<K, T> {
+    pub subjects: Vec<K>,
+    pub collisions: Vec<((K, T), (K, T))>,
+}
+
+impl<K: Display> Display for Infeasibility<K, SubjectCommision> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No combination exists for subjects {}",
+            self.subjects
+                .iter()
+                .map(K::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        for ((_, com1), (_, com2)) in &self.collisions {
+            write!(f, "\n  {com1} and {com2} clash on")?;
+            for (day, span1, span2) in com1.schedule.collisions_with(&com2.schedule) {
+                write!(f, " {day:?} ({span1} / {span2})")?;
+            }
+        }
+        Ok(())
+    }
+}
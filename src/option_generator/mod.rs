@@ -1,80 +1,185 @@
-use crate::models::Collidable;
+use crate::models::{Collidable, DaysOfTheWeek, Overlap, Span};
 use core::hash::Hash;
 use itertools::iproduct;
 use itertools::Either;
 use itertools::Itertools;
-use std::collections::HashSet;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
 use std::iter;
 use std::rc::Rc;
 
+mod cardinality;
+pub use cardinality::Cardinality;
+mod cursor;
+pub use cursor::Cursor;
+mod diversity;
+pub use diversity::schedule_distance;
+mod equivalence;
+pub use equivalence::{schedule_key, Equivalent};
+mod expression;
 pub mod filters;
+pub use expression::{parse_filter, ParseError};
+mod generation;
+use generation::Monitor;
+pub use generation::{CancellationToken, GenerationContext, Progress, StopReason};
+mod infeasibility;
+pub use infeasibility::Infeasibility;
+mod option_iterator;
+pub use option_iterator::OptionIterator;
+mod pareto;
+pub use pareto::{Objective, ParetoOption};
+mod pins;
+mod relaxation;
+mod sampling;
+#[cfg(feature = "scripting")]
+mod scripting;
+#[cfg(feature = "scripting")]
+pub use scripting::{Scored, Script};
+mod soft_constraints;
+pub use relaxation::{Constraints, Relaxation, Suggestion};
+pub use soft_constraints::{Penalized, SoftConstraints};
+mod tolerance;
+pub use tolerance::{OverlapTolerance, ToleratedOverlap};
 
 type CollisionSet<K, T> = HashSet<((K, T), (K, T))>;
+type ToleratedSet<K, T> = HashMap<((K, T), (K, T)), Vec<(DaysOfTheWeek, Span)>>;
+type ToleranceCheck<K, T> = Rc<dyn Fn(&(K, T), &(K, T)) -> Option<Vec<(DaysOfTheWeek, Span)>>>;
 
 fn find_pair_collisions<K: Hash + Eq + Clone, T>(
     vectors: impl Iterator<Item = (K, Vec<T>)>,
-) -> CollisionSet<K, T>
+    tolerance: Option<&ToleranceCheck<K, T>>,
+) -> (CollisionSet<K, T>, ToleratedSet<K, T>)
 where
     T: Collidable + Clone + Hash + Eq,
 {
     let mut out = HashSet::new();
+    let mut tolerated = HashMap::new();
     for pair in vectors.combinations(2) {
         let (key_a, a) = &pair[0];
         let (key_b, b) = &pair[1];
         for (com1, com2) in iproduct!(a.iter(), b.iter()) {
             if com1.collides(com2) {
-                out.insert(((key_a.clone(), com1.clone()), (key_b.clone(), com2.clone())));
+                let pair = ((key_a.clone(), com1.clone()), (key_b.clone(), com2.clone()));
+                if let Some(overlaps) = tolerance.and_then(|tolerance| tolerance(&pair.0, &pair.1))
+                {
+                    tolerated.insert(pair, overlaps);
+                } else {
+                    out.insert(pair);
+                }
             }
         }
     }
-    out
+    (out, tolerated)
+}
+
+pub type CursorIterator<'a, T> = Box<dyn Iterator<Item = (Cursor, Vec<Option<T>>)> + 'a>;
+pub type OverlapIterator<'a, K, T> =
+    Box<dyn Iterator<Item = (Vec<Option<T>>, Vec<ToleratedOverlap<K, T>>)> + 'a>;
+
+type Chosen<K, T> = Vec<(K, usize, Option<T>)>;
+
+struct SearchContext<K, T> {
+    pair_collisions: CollisionSet<K, T>,
+    cardinalities: Vec<Cardinality<K>>,
+    monitor: Option<RefCell<Monitor>>,
+}
+
+impl<K: Hash + Eq + Clone, T: Hash + Eq + Clone> SearchContext<K, T> {
+    fn collides(&self, chosen: &Chosen<K, T>, key: &K, val: &T) -> bool {
+        chosen.iter().any(|(i, _, previous)| {
+            previous.clone().is_some_and(|previous| {
+                self.pair_collisions
+                    .contains(&((i.clone(), previous), (key.clone(), val.clone())))
+            })
+        })
+    }
+
+    fn admits(&self, chosen: &Chosen<K, T>, pending: &[(K, Group<T>)]) -> bool {
+        self.cardinalities
+            .iter()
+            .all(|cardinality| cardinality.admits(chosen, pending))
+    }
+
+    fn visit(&self, chosen: &Chosen<K, T>) -> bool {
+        self.monitor
+            .as_ref()
+            .is_none_or(|monitor| monitor.borrow_mut().visit(chosen))
+    }
+
+    fn found(&self) {
+        if let Some(monitor) = &self.monitor {
+            monitor.borrow_mut().found();
+        }
+    }
+
+    fn finish(&self) {
+        if let Some(monitor) = &self.monitor {
+            monitor.borrow_mut().finish();
+        }
+    }
 }
 
 fn recursive_generate<'a, K: Hash + Eq + Clone + 'a, T: Collidable + Hash + Eq + Clone + 'a>(
-    pair_collisions: Rc<CollisionSet<K, T>>,
-    previously_chosen: Rc<Vec<(K, Option<T>)>>,
+    context: Rc<SearchContext<K, T>>,
+    previously_chosen: Rc<Chosen<K, T>>,
     vectors: Vec<(K, Group<T>)>,
-) -> Box<dyn Iterator<Item = Vec<(K, Option<T>)>> + 'a> {
+    resume_from: Option<Vec<usize>>,
+) -> Box<dyn Iterator<Item = Chosen<K, T>> + 'a> {
     if vectors.is_empty() {
-        return Box::new(iter::once(Vec::clone(&previously_chosen)));
+        // When resuming, the leaf reached by following the cursor was already yielded.
+        return match resume_from {
+            None if context.admits(&previously_chosen, &[]) => {
+                context.found();
+                Box::new(iter::once(Vec::clone(&previously_chosen)))
+            }
+            _ => Box::new(iter::empty()),
+        };
     }
     let (chosen_key, to_choose) = vectors[0].clone();
 
     let collides_with_previous = {
         let previously_chosen = previously_chosen.clone();
         let chosen_key = chosen_key.clone();
-        let pair_collisions = pair_collisions.clone();
-        move |val: T| {
-            previously_chosen.iter().any(|(i, previous)| {
-                previous.clone().map_or(false, |previous| {
-                    pair_collisions
-                        .contains(&((i.clone(), previous), (chosen_key.clone(), val.clone())))
-                })
-            })
-        }
+        let context = context.clone();
+        move |val: &T| context.collides(&previously_chosen, &chosen_key, val)
+    };
+
+    let none_index = to_choose.items.len();
+    let (resume_index, resume_rest) = match resume_from {
+        Some(cursor) => (cursor[0], Some(cursor[1..].to_vec())),
+        None => (0, None),
     };
 
     Box::new(
         to_choose
             .items
             .into_iter()
-            .filter(move |val| !collides_with_previous(val.clone()))
-            .map(Some)
+            .enumerate()
+            .filter(move |(_, val)| !collides_with_previous(val))
+            .map(|(index, val)| (index, Some(val)))
             .chain(if to_choose.mandatory {
                 Either::Left(iter::empty())
             } else {
-                Either::Right(iter::once(None))
+                Either::Right(iter::once((none_index, None)))
             })
+            .filter(move |(index, _)| *index >= resume_index)
             .flat_map({
                 let rest = vectors[1..].to_vec();
-                move |val| {
+                move |(index, val)| {
                     let mut updated_previously_chosen = (*previously_chosen).clone();
-                    updated_previously_chosen.push((chosen_key.clone(), val));
-                    recursive_generate(
-                        pair_collisions.clone(),
+                    updated_previously_chosen.push((chosen_key.clone(), index, val));
+                    if !context.visit(&updated_previously_chosen)
+                        || !context.admits(&updated_previously_chosen, &rest)
+                    {
+                        return Either::Left(iter::empty());
+                    }
+                    Either::Right(recursive_generate(
+                        context.clone(),
                         Rc::new(updated_previously_chosen),
                         rest.clone(),
-                    )
+                        resume_rest.clone().filter(|_| index == resume_index),
+                    ))
                 }
             }),
     )
@@ -101,10 +206,14 @@ impl<T> Group<T> {
     }
 }
 
+#[derive(Clone)]
 pub struct OptionGenerator<K, T> {
     mandatory: Vec<(K, Vec<T>)>,
     optional: Vec<(K, Vec<T>)>,
     collision_exceptions: CollisionSet<K, T>,
+    cardinalities: Vec<Cardinality<K>>,
+    alternatives: Vec<HashSet<K>>,
+    overlap_tolerance: Option<ToleranceCheck<K, T>>,
 }
 
 impl<K, T> Default for OptionGenerator<K, T> {
@@ -113,6 +222,9 @@ impl<K, T> Default for OptionGenerator<K, T> {
             mandatory: vec![],
             optional: vec![],
             collision_exceptions: HashSet::new(),
+            cardinalities: vec![],
+            alternatives: vec![],
+            overlap_tolerance: None,
         }
     }
 }
@@ -136,49 +248,292 @@ impl<'a, K: Hash + Eq + Clone + 'a, T: Collidable + Hash + Eq + Clone + 'a> Opti
         self
     }
 
-    pub fn generate(self) -> Box<dyn Iterator<Item = Vec<Option<T>>> + 'a> {
-        let pair_collisions = find_pair_collisions(
-            self.mandatory
-                .iter()
-                .cloned()
-                .chain(self.optional.iter().cloned()),
+    pub fn set_cardinalities(&mut self, cardinalities: Vec<Cardinality<K>>) -> &mut Self {
+        self.cardinalities = cardinalities;
+        self
+    }
+
+    /// Sets of subjects that are the same class under different keys. At most one subject of
+    /// each set is chosen, and their commissions are not reported as colliding.
+    pub fn set_alternatives(&mut self, alternatives: Vec<HashSet<K>>) -> &mut Self {
+        self.alternatives = alternatives;
+        self
+    }
+
+    /// Drops the items that `keep` rejects before searching, so that e.g. a
+    /// [`TimeWindow`](filters::TimeWindow) prunes commissions instead of whole options.
+    pub fn retain_items(&mut self, keep: impl Fn(&T) -> bool) -> &mut Self {
+        for (_, items) in self.mandatory.iter_mut().chain(self.optional.iter_mut()) {
+            items.retain(&keep);
+        }
+        self
+    }
+
+    fn all_cardinalities(&self) -> Vec<Cardinality<K>> {
+        self.cardinalities
+            .iter()
+            .cloned()
+            .chain(
+                self.alternatives
+                    .iter()
+                    .map(|keys| Cardinality::at_most(keys.iter().cloned(), 1)),
+            )
+            .collect()
+    }
+
+    fn groups(&self) -> impl Iterator<Item = (K, Group<T>)> + '_ {
+        iter::empty()
+            .chain(
+                self.mandatory
+                    .iter()
+                    .map(|(k, items)| (k.clone(), Group::mandatory(items.clone()))),
+            )
+            .chain(
+                self.optional
+                    .iter()
+                    .map(|(k, items)| (k.clone(), Group::optional(items.clone()))),
+            )
+    }
+
+    fn pair_collisions(
+        &self,
+        vectors: impl Iterator<Item = (K, Vec<T>)>,
+    ) -> (CollisionSet<K, T>, ToleratedSet<K, T>) {
+        let (pair_collisions, tolerated) =
+            find_pair_collisions(vectors, self.overlap_tolerance.as_ref());
+        // Exceptions and alternatives apply alike to collisions and tolerated overlaps.
+        let exempt = |(a, b): &((K, T), (K, T))| {
+            self.collision_exceptions.contains(&(a.clone(), b.clone()))
+                || self.collision_exceptions.contains(&(b.clone(), a.clone()))
+                || self
+                    .alternatives
+                    .iter()
+                    .any(|keys| keys.contains(&a.0) && keys.contains(&b.0))
+        };
+        let pair_collisions = pair_collisions
+            .into_iter()
+            .filter(|pair| !exempt(pair))
+            .collect();
+        let tolerated = tolerated
+            .into_iter()
+            .filter(|(pair, _)| !exempt(pair))
+            .collect();
+        (pair_collisions, tolerated)
+    }
+
+    fn all_vectors(&self) -> impl Iterator<Item = (K, Vec<T>)> + '_ {
+        self.mandatory
+            .iter()
+            .cloned()
+            .chain(self.optional.iter().cloned())
+    }
+
+    fn search_context(&self) -> SearchContext<K, T> {
+        SearchContext {
+            pair_collisions: self.pair_collisions(self.all_vectors()).0,
+            cardinalities: self.all_cardinalities(),
+            monitor: None,
+        }
+    }
+
+    fn search(
+        self,
+        resume_from: Option<Cursor>,
+        monitor: Option<Monitor>,
+    ) -> Box<dyn Iterator<Item = Chosen<K, T>> + 'a> {
+        let groups = self.groups().collect::<Vec<_>>();
+        let mut context = self.search_context();
+        context.monitor = monitor.map(RefCell::new);
+        let context = Rc::new(context);
+
+        let chosen = recursive_generate(
+            context.clone(),
+            Rc::new(vec![]),
+            groups,
+            resume_from.map(|cursor| cursor.0),
         );
-        let pair_collisions = HashSet::from_iter(
-            pair_collisions
-                .difference(&HashSet::from_iter(
-                    self.collision_exceptions
+        Box::new(chosen.chain(iter::from_fn(move || {
+            context.finish();
+            None
+        })))
+    }
+
+    fn with_cursors(chosen: impl Iterator<Item = Chosen<K, T>> + 'a) -> CursorIterator<'a, T> {
+        Box::new(
+            chosen
+                .map(|choice| {
+                    choice
                         .into_iter()
-                        .flat_map(|v| [v.clone(), (v.1, v.0)]),
-                ))
-                .cloned(),
-        );
+                        .map(|(_, index, o)| (index, o))
+                        .unzip::<_, _, Vec<_>, _>()
+                })
+                .map(|(indices, choice)| (Cursor(indices), choice)),
+        )
+    }
 
+    pub fn generate(self) -> Box<dyn Iterator<Item = Vec<Option<T>>> + 'a> {
         Box::new(
-            recursive_generate(
-                Rc::new(pair_collisions),
-                Rc::new(vec![]),
-                iter::empty()
-                    .chain(
-                        self.mandatory
-                            .into_iter()
-                            .map(|(k, items)| (k, Group::mandatory(items))),
-                    )
-                    .chain(
-                        self.optional
-                            .into_iter()
-                            .map(|(k, items)| (k, Group::optional(items))),
-                    )
-                    .collect::<Vec<_>>(),
+            self.search(None, None)
+                .map(|choice| choice.into_iter().map(|(_, _, o)| o).collect()),
+        )
+    }
+
+    /// Like [`generate`](Self::generate), but stops early when `context` says so and reports
+    /// progress along the way.
+    pub fn generate_with_context(
+        self,
+        context: GenerationContext,
+    ) -> Box<dyn Iterator<Item = Vec<Option<T>>> + 'a> {
+        let branches = self
+            .groups()
+            .map(|(_, group)| group.items.len() + usize::from(!group.mandatory))
+            .collect();
+        let monitor = Monitor::new(context, branches);
+        Box::new(
+            self.search(None, Some(monitor))
+                .map(|choice| choice.into_iter().map(|(_, _, o)| o).collect()),
+        )
+    }
+
+    pub fn generate_with_cursors(self) -> CursorIterator<'a, T> {
+        Self::with_cursors(self.search(None, None))
+    }
+
+    /// Like [`generate`](Self::generate), also listing the overlaps that were let through by
+    /// the [`OverlapTolerance`] in every option.
+    pub fn generate_with_overlaps(self) -> OverlapIterator<'a, K, T> {
+        let (_, tolerated) = self.pair_collisions(self.all_vectors());
+        Box::new(self.search(None, None).map(move |choice| {
+            let overlaps = choice
+                .iter()
+                .filter_map(|(k, _, o)| Some((k.clone(), o.clone()?)))
+                .tuple_combinations()
+                .filter_map(|(first, second)| {
+                    let overlaps = tolerated.get(&(first.clone(), second.clone()))?;
+                    Some(ToleratedOverlap {
+                        first,
+                        second,
+                        overlaps: overlaps.clone(),
+                    })
+                })
+                .collect();
+            (choice.into_iter().map(|(_, _, o)| o).collect(), overlaps)
+        }))
+    }
+
+    pub fn resume(self, cursor: &Cursor) -> Result<CursorIterator<'a, T>, Box<dyn Error>> {
+        let groups = self.groups().collect::<Vec<_>>();
+        if cursor.0.len() != groups.len() {
+            return Err(format!(
+                "Cursor has {} positions but the generator has {} groups.",
+                cursor.0.len(),
+                groups.len()
             )
-            .map(|choice| choice.into_iter().map(|(_, o)| o).collect()),
+            .into());
+        }
+        for (&index, (_, group)) in cursor.0.iter().zip(&groups) {
+            if index > group.items.len() || (group.mandatory && index == group.items.len()) {
+                return Err(format!("Cursor position {index} is out of range.").into());
+            }
+        }
+        Ok(Self::with_cursors(self.search(Some(cursor.clone()), None)))
+    }
+
+    fn is_feasible(&self, mandatory: &[(K, Vec<T>)]) -> bool {
+        // Subjects left out of `mandatory` may still be taken, so a lower bound only holds when
+        // every key it counts is in the set. Upper bounds hold for any subset.
+        let cardinalities = self
+            .all_cardinalities()
+            .into_iter()
+            .map(|cardinality| {
+                if cardinality
+                    .keys
+                    .iter()
+                    .all(|key| mandatory.iter().any(|(k, _)| k == key))
+                {
+                    cardinality
+                } else {
+                    Cardinality::new(cardinality.keys, 0..=*cardinality.range.end())
+                }
+            })
+            .collect();
+        recursive_generate(
+            Rc::new(SearchContext {
+                pair_collisions: self.pair_collisions(mandatory.iter().cloned()).0,
+                cardinalities,
+                monitor: None,
+            }),
+            Rc::new(vec![]),
+            mandatory
+                .iter()
+                .map(|(k, items)| (k.clone(), Group::mandatory(items.clone())))
+                .collect(),
+            None,
         )
+        .next()
+        .is_some()
+    }
+
+    pub fn explain_infeasible(&self) -> Option<Infeasibility<K, T>> {
+        if self.is_feasible(&self.mandatory) {
+            return None;
+        }
+
+        // Drop every subject that is not needed to keep the set infeasible.
+        let mut conflicting = self.mandatory.clone();
+        let mut i = 0;
+        while i < conflicting.len() {
+            let mut without = conflicting.clone();
+            without.remove(i);
+            if self.is_feasible(&without) {
+                i += 1;
+            } else {
+                conflicting = without;
+            }
+        }
+
+        let (collisions, _) = self.pair_collisions(conflicting.iter().cloned());
+        let collisions = conflicting
+            .iter()
+            .tuple_combinations()
+            .flat_map(|((key_a, a), (key_b, b))| {
+                iproduct!(a, b).map(move |(com1, com2)| {
+                    ((key_a.clone(), com1.clone()), (key_b.clone(), com2.clone()))
+                })
+            })
+            .filter(|pair| collisions.contains(pair))
+            .collect();
+
+        Some(Infeasibility {
+            subjects: conflicting.into_iter().map(|(k, _)| k).collect(),
+            collisions,
+        })
+    }
+}
+
+impl<K, T> OptionGenerator<K, T>
+where
+    K: Hash + Eq + Clone + 'static,
+    T: Collidable + Overlap + Hash + Eq + Clone + 'static,
+{
+    pub fn set_overlap_tolerance(&mut self, tolerance: OverlapTolerance<K>) -> &mut Self {
+        self.overlap_tolerance = Some(Rc::new(move |(key_a, a), (key_b, b)| {
+            let overlaps = a.overlaps(b);
+            let tolerated = !overlaps.is_empty()
+                && overlaps
+                    .iter()
+                    .all(|(day, overlap)| tolerance.allows(key_a, key_b, *day, overlap));
+            tolerated.then_some(overlaps)
+        }));
+        self
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{models::Span, t};
+    use crate::models::{Day, Task, Week};
+    use crate::t;
 
     #[test]
     fn generate_test() {
@@ -210,4 +565,245 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn resume_test() {
+        let sa = Span::new(t!("00:00"), t!("01:00"));
+        let sb = Span::new(t!("01:00"), t!("02:00"));
+        let sc = Span::new(t!("02:00"), t!("03:00"));
+        let generator = || {
+            let mut generator = OptionGenerator::default();
+            generator
+                .set_mandatory(vec![("0", vec![sa, sc])])
+                .set_optional(vec![("1", vec![sa, sb, sc]), ("2", vec![sa, sb])]);
+            generator
+        };
+        let all = generator().generate().collect_vec();
+
+        let mut pages = vec![];
+        let mut cursor: Option<Cursor> = None;
+        loop {
+            let page = match &cursor {
+                Some(cursor) => generator().resume(cursor).unwrap(),
+                None => generator().generate_with_cursors(),
+            }
+            .take(4)
+            .collect_vec();
+            match page.last() {
+                Some((last, _)) => cursor = Some(last.to_string().parse().unwrap()),
+                None => break,
+            }
+            pages.extend(page.into_iter().map(|(_, choice)| choice));
+        }
+        assert_eq!(pages, all);
+
+        assert!(generator().resume(&Cursor(vec![0, 0])).is_err());
+        assert!(generator().resume(&Cursor(vec![2, 0, 0])).is_err());
+        assert!(generator().resume(&Cursor(vec![1, 3, 2])).is_ok());
+    }
+
+    #[test]
+    fn explain_infeasible_test() {
+        let sa = Span::new(t!("00:00"), t!("01:00"));
+        let sb = Span::new(t!("01:00"), t!("02:00"));
+        let sc = Span::new(t!("00:30"), t!("01:30"));
+        let mut generator = OptionGenerator::default();
+        generator.set_mandatory(vec![("0", vec![sa, sb]), ("1", vec![sb])]);
+        assert_eq!(generator.explain_infeasible(), None);
+
+        generator.set_mandatory(vec![
+            ("0", vec![sa, sb]),
+            ("1", vec![sb]),
+            ("2", vec![sb]),
+            ("3", vec![sc]),
+        ]);
+        assert_eq!(
+            generator.explain_infeasible(),
+            Some(Infeasibility {
+                subjects: vec!["2", "3"],
+                collisions: vec![(("2", sb), ("3", sc))],
+            })
+        );
+
+        generator.set_mandatory(vec![("0", vec![sa, sb]), ("1", vec![sa]), ("2", vec![sb])]);
+        assert_eq!(
+            generator.explain_infeasible(),
+            Some(Infeasibility {
+                subjects: vec!["0", "1", "2"],
+                collisions: vec![(("0", sa), ("1", sa)), (("0", sb), ("2", sb))],
+            })
+        );
+
+        generator.set_collission_exceptions(HashSet::from([(("0", sb), ("2", sb))]));
+        assert_eq!(generator.explain_infeasible(), None);
+
+        // A lower bound on optional subjects is not a conflict between the mandatory ones.
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![("0", vec![sa])])
+            .set_optional(vec![("1", vec![sb])])
+            .set_cardinalities(vec![Cardinality::at_least(["1"], 1)]);
+        assert_eq!(generator.explain_infeasible(), None);
+
+        generator.set_cardinalities(vec![Cardinality::at_most(["0", "1"], 0)]);
+        assert_eq!(
+            generator.explain_infeasible(),
+            Some(Infeasibility {
+                subjects: vec!["0"],
+                collisions: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn cardinality_test() {
+        let sa = Span::new(t!("00:00"), t!("01:00"));
+        let sb = Span::new(t!("01:00"), t!("02:00"));
+        let sc = Span::new(t!("02:00"), t!("03:00"));
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![("0", vec![sa])])
+            .set_optional(vec![("1", vec![sb]), ("2", vec![sc]), ("3", vec![sa, sc])])
+            .set_cardinalities(vec![
+                Cardinality::exactly(["1", "2", "3"], 2),
+                Cardinality::at_least(["0", "1"], 2),
+            ]);
+        assert_eq!(
+            generator.generate().collect_vec(),
+            vec![
+                vec![Some(sa), Some(sb), Some(sc), None],
+                vec![Some(sa), Some(sb), None, Some(sc)],
+            ]
+        );
+
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![("0", vec![sa]), ("1", vec![sb])])
+            .set_cardinalities(vec![Cardinality::at_most(["0", "1"], 1)]);
+        assert_eq!(generator.generate().count(), 0);
+    }
+
+    #[test]
+    fn overlap_tolerance_test() {
+        let week = |day: DaysOfTheWeek, start: &str, end: &str| {
+            let mut week = Week::<()>::empty();
+            week.days[day] = Day::new(vec![Task::new(
+                Span::new(start.parse().unwrap(), end.parse().unwrap()),
+                (),
+            )]);
+            week
+        };
+        let wa = week(DaysOfTheWeek::Monday, "08:00", "10:00");
+        let wb = week(DaysOfTheWeek::Monday, "09:45", "11:00");
+        let wc = week(DaysOfTheWeek::Friday, "08:00", "10:00");
+        let wd = week(DaysOfTheWeek::Friday, "09:30", "11:00");
+        let generator = || {
+            let mut generator = OptionGenerator::default();
+            generator
+                .set_mandatory(vec![("0", vec![wa.clone()]), ("1", vec![wc.clone()])])
+                .set_optional(vec![("2", vec![wb.clone()]), ("3", vec![wd.clone()])]);
+            generator
+        };
+
+        assert_eq!(generator().generate().count(), 1);
+
+        let mut tolerance = OverlapTolerance::new(15);
+        let options = generator()
+            .set_overlap_tolerance(tolerance.clone())
+            .clone()
+            .generate_with_overlaps()
+            .collect_vec();
+        assert_eq!(options.len(), 2);
+        assert_eq!(
+            options[0].0,
+            vec![Some(wa.clone()), Some(wc.clone()), Some(wb.clone()), None]
+        );
+        assert_eq!(
+            options[0].1,
+            vec![ToleratedOverlap {
+                first: ("0", wa.clone()),
+                second: ("2", wb.clone()),
+                overlaps: vec![(DaysOfTheWeek::Monday, Span::new(t!("09:45"), t!("10:00")))],
+            }]
+        );
+        assert!(options[1].1.is_empty());
+
+        let options = generator()
+            .set_overlap_tolerance(tolerance.clone())
+            .set_collission_exceptions(HashSet::from([(("2", wb.clone()), ("0", wa.clone()))]))
+            .clone()
+            .generate_with_overlaps()
+            .collect_vec();
+        assert_eq!(options.len(), 2);
+        assert!(options.iter().all(|(_, overlaps)| overlaps.is_empty()));
+
+        tolerance.set_day(DaysOfTheWeek::Friday, 30);
+        assert_eq!(
+            generator()
+                .set_overlap_tolerance(tolerance.clone())
+                .clone()
+                .generate()
+                .count(),
+            4
+        );
+
+        tolerance.set_day(DaysOfTheWeek::Monday, 0);
+        assert_eq!(
+            generator()
+                .set_overlap_tolerance(tolerance.clone())
+                .clone()
+                .generate()
+                .count(),
+            2
+        );
+        tolerance.set_subject("3", 0);
+        assert_eq!(
+            generator()
+                .set_overlap_tolerance(tolerance.clone())
+                .clone()
+                .generate()
+                .count(),
+            1
+        );
+
+        let mut tolerance = OverlapTolerance::new(0);
+        tolerance.set_subject("3", 30);
+        assert_eq!(
+            generator()
+                .set_overlap_tolerance(tolerance)
+                .clone()
+                .generate()
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn alternatives_test() {
+        let sa = Span::new(t!("00:00"), t!("01:00"));
+        let sb = Span::new(t!("01:00"), t!("02:00"));
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![("0", vec![sa])])
+            .set_optional(vec![("1", vec![sa]), ("2", vec![sb])])
+            .set_alternatives(vec![HashSet::from(["0", "1"])]);
+        assert!(generator
+            .pair_collisions(generator.all_vectors())
+            .0
+            .is_empty());
+        assert_eq!(
+            generator.clone().generate().collect_vec(),
+            vec![vec![Some(sa), None, Some(sb)], vec![Some(sa), None, None]]
+        );
+
+        generator.set_mandatory(vec![("0", vec![sa]), ("1", vec![sa])]);
+        generator.set_optional(vec![]);
+        assert_eq!(
+            generator.explain_infeasible(),
+            Some(Infeasibility {
+                subjects: vec!["0", "1"],
+                collisions: vec![],
+            })
+        );
+    }
 }
@@ -0,0 +1,58 @@
+use super::Equivalent;
+use core::hash::Hash;
+use std::collections::HashMap;
+
+pub trait OptionIterator<T>: Iterator<Item = Vec<Option<T>>> {
+    /// Collapses options with the same key into a single [`Equivalent`], in order of first
+    /// appearance. Every option has to be generated before the first group is known.
+    fn collapse_equivalent<Key, F>(self, key: F) -> Vec<Equivalent<T>>
+    where
+        Self: Sized,
+        Key: Hash + Eq,
+        F: Fn(&[Option<T>]) -> Key,
+    {
+        let mut positions = HashMap::new();
+        let mut groups: Vec<Equivalent<T>> = vec![];
+        for option in self {
+            let position = *positions.entry(key(&option)).or_insert_with(|| {
+                groups.push(Equivalent { options: vec![] });
+                groups.len() - 1
+            });
+            groups[position].options.push(option);
+        }
+        groups
+    }
+
+    /// Picks up to `count` options that are as different from each other as possible, by
+    /// repeatedly taking the option farthest from every option taken so far. The options come
+    /// from this iterator, so a [`sample`](super::OptionGenerator::sample) makes a better pool
+    /// than the first options of a generation, which mostly differ in the last subject.
+    fn most_diverse<F>(self, count: usize, distance: F) -> Vec<Vec<Option<T>>>
+    where
+        Self: Sized,
+        F: Fn(&[Option<T>], &[Option<T>]) -> u32,
+    {
+        let mut pool = self.map(Some).collect::<Vec<_>>();
+        let mut closest = vec![u32::MAX; pool.len()];
+        let mut chosen = vec![];
+        while chosen.len() < count {
+            let Some(farthest) = (0..pool.len())
+                .filter(|&i| pool[i].is_some())
+                .rev()
+                .max_by_key(|&i| closest[i])
+            else {
+                break;
+            };
+            let option = pool[farthest].take().unwrap();
+            for (i, other) in pool.iter().enumerate() {
+                if let Some(other) = other {
+                    closest[i] = closest[i].min(distance(&option, other));
+                }
+            }
+            chosen.push(option);
+        }
+        chosen
+    }
+}
+
+impl<T, I: Iterator<Item = Vec<Option<T>>>> OptionIterator<T> for I {}
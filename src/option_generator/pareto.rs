@@ -0,0 +1,226 @@
+use super::filters::Credited;
+use super::{Chosen, Group, OptionGenerator, SearchContext};
+use crate::models::{Combinable, SubjectCommision, TaskInfo, Week};
+use core::hash::Hash;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    /// More credits are better.
+    Credits,
+    /// Fewer days with classes are better.
+    CampusDays,
+    /// Fewer minutes between classes of the same day are better.
+    IdleMinutes,
+    /// A later first class of the week is better.
+    LatestStart,
+}
+
+impl Objective {
+    fn maximize(self) -> bool {
+        matches!(self, Objective::Credits | Objective::LatestStart)
+    }
+
+    fn value(self, credits: u32, week: &Week<TaskInfo>) -> u64 {
+        let days = week.days.values().filter(|day| !day.tasks.is_empty());
+        match self {
+            Objective::Credits => credits as u64,
+            Objective::CampusDays => days.count() as u64,
+            Objective::IdleMinutes => days
+                .flat_map(|day| {
+                    day.tasks
+                        .windows(2)
+                        .map(|pair| pair[1].span.start.max(pair[0].span.end) - pair[0].span.end)
+                })
+                .sum(),
+            Objective::LatestStart => days
+                .filter_map(|day| day.tasks.first())
+                .map(|task| task.span.start.minutes_since_midnight())
+                .min()
+                .unwrap_or(24 * 60),
+        }
+    }
+
+    /// The best value any completion of a partial choice could reach.
+    fn bound(self, credits: u32, pending_credits: u32, week: &Week<TaskInfo>) -> u64 {
+        match self {
+            Objective::Credits => (credits + pending_credits) as u64,
+            // Adding a class can fill a gap, so idle time is not bounded by the partial choice.
+            Objective::IdleMinutes => 0,
+            Objective::CampusDays | Objective::LatestStart => self.value(credits, week),
+        }
+    }
+}
+
+/// An option that no other option beats in every objective.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParetoOption {
+    pub option: Vec<Option<SubjectCommision>>,
+    /// The value of every objective, in the order they were asked for.
+    pub values: Vec<u64>,
+}
+
+fn dominates(objectives: &[Objective], a: &[u64], b: &[u64]) -> bool {
+    let mut strictly = false;
+    for ((objective, a), b) in objectives.iter().zip(a).zip(b) {
+        let (better, worse) = match objective.maximize() {
+            true => (a > b, a < b),
+            false => (a < b, a > b),
+        };
+        if worse {
+            return false;
+        }
+        strictly |= better;
+    }
+    strictly
+}
+
+struct ParetoSearch<'s, K> {
+    context: SearchContext<K, SubjectCommision>,
+    groups: &'s [(K, Group<SubjectCommision>)],
+    /// Most credits that the groups from each position onwards can add.
+    pending_credits: Vec<u32>,
+    objectives: &'s [Objective],
+    front: Vec<ParetoOption>,
+}
+
+impl<K: Hash + Eq + Clone> ParetoSearch<'_, K> {
+    fn visit(
+        &mut self,
+        chosen: &mut Chosen<K, SubjectCommision>,
+        week: &Week<TaskInfo>,
+        credits: u32,
+    ) {
+        let position = chosen.len();
+        let bound = self
+            .objectives
+            .iter()
+            .map(|objective| objective.bound(credits, self.pending_credits[position], week))
+            .collect::<Vec<_>>();
+        if self
+            .front
+            .iter()
+            .any(|found| dominates(self.objectives, &found.values, &bound))
+        {
+            return;
+        }
+
+        let Some((key, group)) = self.groups.get(position) else {
+            let values = self
+                .objectives
+                .iter()
+                .map(|objective| objective.value(credits, week))
+                .collect::<Vec<_>>();
+            self.front
+                .retain(|found| !dominates(self.objectives, &values, &found.values));
+            self.front.push(ParetoOption {
+                option: chosen.iter().map(|(_, _, val)| val.clone()).collect(),
+                values,
+            });
+            return;
+        };
+
+        let candidates = group
+            .items
+            .iter()
+            .enumerate()
+            .map(|(index, val)| (index, Some(val)))
+            .chain((!group.mandatory).then_some((group.items.len(), None)));
+        for (index, val) in candidates {
+            if val.is_some_and(|val| self.context.collides(chosen, key, val)) {
+                continue;
+            }
+            chosen.push((key.clone(), index, val.cloned()));
+            if self.context.admits(chosen, &self.groups[position + 1..]) {
+                match val {
+                    Some(val) => {
+                        let week = week.combine(&val.schedule);
+                        self.visit(chosen, &week, credits + val.credits());
+                    }
+                    None => self.visit(chosen, week, credits),
+                }
+            }
+            chosen.pop();
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone> OptionGenerator<K, SubjectCommision> {
+    /// Every option that is not beaten in all `objectives` at once by another option. Branches
+    /// whose best possible outcome is already beaten are not explored.
+    pub fn pareto_front(&self, objectives: &[Objective]) -> Vec<ParetoOption> {
+        let groups = self.groups().collect::<Vec<_>>();
+        let mut pending_credits = groups
+            .iter()
+            .rev()
+            .scan(0, |pending, (_, group)| {
+                *pending += group.items.iter().map(Credited::credits).max().unwrap_or(0);
+                Some(*pending)
+            })
+            .collect::<Vec<_>>();
+        pending_credits.reverse();
+        pending_credits.push(0);
+
+        let mut search = ParetoSearch {
+            context: self.search_context(),
+            groups: &groups,
+            pending_credits,
+            objectives,
+            front: vec![],
+        };
+        search.visit(&mut vec![], &Week::empty(), 0);
+        search.front
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{class, entry, label, SubjectBuilder};
+    use crate::models::DaysOfTheWeek;
+    use itertools::Itertools;
+
+    #[test]
+    fn pareto_front_test() {
+        let subjects = [
+            SubjectBuilder::new("01.01")
+                .credits(3)
+                .commissions(&[
+                    class(DaysOfTheWeek::Monday, "08:00", "10:00"),
+                    class(DaysOfTheWeek::Tuesday, "14:00", "16:00"),
+                ])
+                .build(),
+            SubjectBuilder::new("01.02")
+                .credits(6)
+                .commissions(&[class(DaysOfTheWeek::Monday, "12:00", "14:00")])
+                .build(),
+        ];
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![entry(&subjects[0])])
+            .set_optional(vec![entry(&subjects[1])]);
+
+        let front = |objectives: &[Objective]| {
+            generator
+                .pareto_front(objectives)
+                .into_iter()
+                .map(|pareto| (label(&pareto.option), pareto.values))
+                .collect_vec()
+        };
+
+        assert_eq!(
+            front(&[Objective::Credits, Objective::CampusDays]),
+            vec![("00".to_owned(), vec![9, 1])]
+        );
+        assert_eq!(
+            front(&[Objective::IdleMinutes, Objective::CampusDays]),
+            vec![("0-".to_owned(), vec![0, 1]), ("1-".to_owned(), vec![0, 1])]
+        );
+        assert_eq!(
+            front(&[Objective::Credits, Objective::LatestStart]),
+            vec![
+                ("10".to_owned(), vec![9, 720]),
+                ("1-".to_owned(), vec![3, 840])
+            ]
+        );
+    }
+}
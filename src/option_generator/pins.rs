@@ -0,0 +1,207 @@
+use super::OptionGenerator;
+use crate::models::SubjectCommision;
+use core::hash::Hash;
+use std::error::Error;
+use std::fmt::Display;
+
+impl<K: Hash + Eq + Clone + Display> OptionGenerator<K, SubjectCommision> {
+    fn commissions_mut(
+        &mut self,
+        key: &K,
+        name: &str,
+    ) -> Result<&mut Vec<SubjectCommision>, Box<dyn Error>> {
+        let commissions = self
+            .mandatory
+            .iter_mut()
+            .chain(self.optional.iter_mut())
+            .find(|(k, _)| k == key)
+            .map(|(_, commissions)| commissions)
+            .ok_or_else(|| format!("Unknown subject {key}."))?;
+        if !commissions
+            .iter()
+            .any(|c| c.names.iter().any(|n| n == name))
+        {
+            return Err(format!("Subject {key} has no commission named {name:?}.").into());
+        }
+        Ok(commissions)
+    }
+
+    /// Restricts the subject to the commission with the given name. Pinning an optional subject
+    /// makes it mandatory, since asking for a commission means the subject is wanted.
+    pub fn pin_commission(&mut self, key: &K, name: &str) -> Result<&mut Self, Box<dyn Error>> {
+        self.commissions_mut(key, name)?
+            .retain(|c| c.names.iter().any(|n| n == name));
+        if let Some(index) = self.optional.iter().position(|(k, _)| k == key) {
+            let subject = self.optional.remove(index);
+            self.mandatory.push(subject);
+        }
+        Ok(self)
+    }
+
+    /// Removes the commission with the given name from the subject. Commissions merged by
+    /// [`Subject::optimize`](crate::models::Subject::optimize) keep their other names, while
+    /// [bundles](crate::models::Subject::bundles) that include it are dropped as a whole.
+    pub fn exclude_commission(&mut self, key: &K, name: &str) -> Result<&mut Self, Box<dyn Error>> {
+        let commissions = self.commissions_mut(key, name)?;
+        commissions.retain_mut(|c| {
+            if !c.names.iter().any(|n| n == name) {
+                return true;
+            }
+            // Names from the same component share a schedule, so another one standing in for the
+            // excluded name keeps the commission. Names from other components don't.
+            let subject = c.subject.upgrade();
+            let component_of = |n: &str| {
+                subject
+                    .as_ref()
+                    .and_then(|s| s.borrow().component_of_name(n))
+            };
+            let component = component_of(name);
+            c.names.retain(|n| n != name);
+            c.names.iter().any(|n| component_of(n) == component)
+        });
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{entry, SubjectBuilder};
+    use crate::models::Code;
+
+    fn names(generator: &OptionGenerator<Code, SubjectCommision>) -> Vec<Vec<Vec<String>>> {
+        generator
+            .mandatory
+            .iter()
+            .chain(&generator.optional)
+            .map(|(_, coms)| coms.iter().map(|c| c.names.clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn pin_and_exclude() {
+        let subjects = [
+            SubjectBuilder::new("72.11")
+                .commission("R", &[])
+                .commission("S", &[])
+                .also_named("T")
+                .build(),
+            SubjectBuilder::new("93.41")
+                .commission("A", &[])
+                .commission("B", &[])
+                .build(),
+        ];
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![entry(&subjects[0])])
+            .set_optional(vec![entry(&subjects[1])]);
+
+        generator
+            .pin_commission(&"72.11".parse().unwrap(), "S")
+            .unwrap()
+            .exclude_commission(&"93.41".parse().unwrap(), "A")
+            .unwrap();
+        assert_eq!(
+            (generator.mandatory.len(), generator.optional.len()),
+            (1, 1)
+        );
+        assert_eq!(
+            names(&generator),
+            vec![
+                vec![vec!["S".to_owned(), "T".to_owned()]],
+                vec![vec!["B".to_owned()]]
+            ]
+        );
+
+        generator
+            .exclude_commission(&"72.11".parse().unwrap(), "T")
+            .unwrap();
+        assert_eq!(names(&generator)[0], vec![vec!["S".to_owned()]]);
+
+        assert_eq!(
+            generator
+                .pin_commission(&"72.11".parse().unwrap(), "R")
+                .err()
+                .unwrap()
+                .to_string(),
+            "Subject 72.11 has no commission named \"R\"."
+        );
+        assert_eq!(
+            generator
+                .exclude_commission(&"10.01".parse().unwrap(), "A")
+                .err()
+                .unwrap()
+                .to_string(),
+            "Unknown subject 10.01."
+        );
+    }
+
+    #[test]
+    fn pinning_an_optional_subject_makes_it_mandatory() {
+        let subjects = [
+            SubjectBuilder::new("72.11").commission("R", &[]).build(),
+            SubjectBuilder::new("93.41")
+                .commission("A", &[])
+                .commission("B", &[])
+                .build(),
+        ];
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![entry(&subjects[0])])
+            .set_optional(vec![entry(&subjects[1])])
+            .pin_commission(&"93.41".parse().unwrap(), "B")
+            .unwrap();
+
+        assert!(generator.optional.is_empty());
+        assert_eq!(
+            names(&generator),
+            vec![vec![vec!["R".to_owned()]], vec![vec!["B".to_owned()]]]
+        );
+        assert!(generator.generate().all(|option| option.len() == 2));
+    }
+
+    #[test]
+    fn exclude_component_of_bundle() {
+        let subject = SubjectBuilder::new("72.11")
+            .commission("T1", &[])
+            .commission("T2", &[])
+            .commission("L1", &[])
+            .also_named("L2")
+            .components(&[&["T1", "T2"], &["L1", "L2"]])
+            .build();
+        let bundles = |generator: &mut OptionGenerator<Code, SubjectCommision>| {
+            generator.set_mandatory(vec![(subject.borrow().code, subject.borrow().bundles())]);
+        };
+        let code = "72.11".parse().unwrap();
+
+        let mut generator = OptionGenerator::default();
+        bundles(&mut generator);
+        generator.exclude_commission(&code, "T1").unwrap();
+        assert_eq!(
+            names(&generator),
+            vec![vec![vec![
+                "T2".to_owned(),
+                "L1".to_owned(),
+                "L2".to_owned()
+            ]]]
+        );
+
+        generator.exclude_commission(&code, "L1").unwrap();
+        assert_eq!(
+            names(&generator),
+            vec![vec![vec!["T2".to_owned(), "L2".to_owned()]]]
+        );
+
+        let mut generator = OptionGenerator::default();
+        bundles(&mut generator);
+        generator.pin_commission(&code, "T2").unwrap();
+        assert_eq!(
+            names(&generator),
+            vec![vec![vec![
+                "T2".to_owned(),
+                "L1".to_owned(),
+                "L2".to_owned()
+            ]]]
+        );
+    }
+}
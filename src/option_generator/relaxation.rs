@@ -0,0 +1,280 @@
+use super::filters::{ChoiceFilter, ChoiceIterator, CreditCount, SubjectCount};
+use super::OptionGenerator;
+use crate::models::SubjectCommision;
+use core::hash::Hash;
+use std::cmp::Reverse;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraints {
+    pub credits: RangeInclusive<u32>,
+    pub subjects: RangeInclusive<u32>,
+}
+
+impl Constraints {
+    fn count_options<K: Hash + Eq + Clone>(
+        &self,
+        generator: OptionGenerator<K, SubjectCommision>,
+        limit: usize,
+        filter: &impl ChoiceFilter<SubjectCommision>,
+    ) -> usize {
+        generator
+            .generate()
+            .filter_choices(SubjectCount::new(self.subjects.clone()))
+            .filter_choices(CreditCount::new(self.credits.clone()))
+            .filter_choices(|option: &Vec<_>| filter.filter(option))
+            .take(limit)
+            .count()
+    }
+}
+
+/// Only this many overlap relaxations are tried, since each one runs a full generation.
+const MAX_OVERLAP_RELAXATIONS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Relaxation<K> {
+    Credits(RangeInclusive<u32>),
+    Subjects(RangeInclusive<u32>),
+    DropSubject(K),
+    AllowOverlap(Box<(K, SubjectCommision)>, Box<(K, SubjectCommision)>),
+}
+
+impl<K: Display> Display for Relaxation<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Relaxation::Credits(range) => {
+                write!(f, "Allow {} to {} credits", range.start(), range.end())
+            }
+            Relaxation::Subjects(range) => {
+                write!(f, "Allow {} to {} subjects", range.start(), range.end())
+            }
+            Relaxation::DropSubject(key) => write!(f, "Drop subject {key}"),
+            Relaxation::AllowOverlap(a, b) => {
+                write!(f, "Allow {} to overlap with {}", a.1, b.1)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion<K> {
+    pub relaxation: Relaxation<K>,
+    pub options: usize,
+}
+
+impl<K: Hash + Eq + Clone> OptionGenerator<K, SubjectCommision> {
+    fn relaxations(&self, constraints: &Constraints) -> Vec<Relaxation<K>> {
+        let mut relaxations = vec![];
+
+        // Widening the credit range by the smallest subject is the least change that can let
+        // one more or one less subject in.
+        let credit_step = self
+            .mandatory
+            .iter()
+            .chain(&self.optional)
+            .flat_map(|(_, commissions)| commissions)
+            .map(|c| c.subject.upgrade().unwrap().borrow().credits as u32)
+            .filter(|&credits| credits > 0)
+            .min()
+            .unwrap_or(1);
+        for (range, step, relaxation) in [
+            (
+                &constraints.credits,
+                credit_step,
+                Relaxation::Credits as fn(_) -> _,
+            ),
+            (&constraints.subjects, 1, Relaxation::Subjects),
+        ] {
+            let (start, end) = (*range.start(), *range.end());
+            if start > 0 {
+                relaxations.push(relaxation(start.saturating_sub(step)..=end));
+            }
+            relaxations.push(relaxation(start..=end + step));
+        }
+
+        relaxations.extend(
+            self.mandatory
+                .iter()
+                .map(|(key, _)| Relaxation::DropSubject(key.clone())),
+        );
+
+        // Collisions between two optional subjects never rule a mandatory one out, so only the
+        // ones touching a mandatory subject are worth relaxing, mandatory pairs first.
+        let is_mandatory = |key: &K| self.mandatory.iter().any(|(k, _)| k == key);
+        let mut overlaps = self
+            .pair_collisions(self.all_vectors())
+            .0
+            .into_iter()
+            .map(|(a, b)| {
+                let mandatory = [&a.0, &b.0].into_iter().filter(|k| is_mandatory(k)).count();
+                (mandatory, a, b)
+            })
+            .filter(|(mandatory, _, _)| *mandatory > 0)
+            .collect::<Vec<_>>();
+        overlaps.sort_by_key(|(mandatory, (_, com1), (_, com2))| {
+            (Reverse(*mandatory), com1.to_string(), com2.to_string())
+        });
+        relaxations.extend(
+            overlaps
+                .into_iter()
+                .take(MAX_OVERLAP_RELAXATIONS)
+                .map(|(_, a, b)| Relaxation::AllowOverlap(Box::new(a), Box::new(b))),
+        );
+
+        relaxations
+    }
+
+    /// Tries every single relaxation of the constraints and returns the ones that produce any
+    /// option, best first. Options are only counted up to `limit` for each relaxation.
+    pub fn suggest_relaxations(
+        &self,
+        constraints: &Constraints,
+        limit: usize,
+    ) -> Vec<Suggestion<K>> {
+        self.suggest_relaxations_with(constraints, limit, |_: &Vec<_>| true)
+    }
+
+    /// Like [`suggest_relaxations`](Self::suggest_relaxations), but only counts the options
+    /// that also pass `filter`, which is not relaxed.
+    pub fn suggest_relaxations_with(
+        &self,
+        constraints: &Constraints,
+        limit: usize,
+        filter: impl ChoiceFilter<SubjectCommision>,
+    ) -> Vec<Suggestion<K>> {
+        let mut suggestions = self
+            .relaxations(constraints)
+            .into_iter()
+            .map(|relaxation| {
+                let mut generator = self.clone();
+                let mut constraints = constraints.clone();
+                match &relaxation {
+                    Relaxation::Credits(range) => constraints.credits = range.clone(),
+                    Relaxation::Subjects(range) => constraints.subjects = range.clone(),
+                    Relaxation::DropSubject(key) => generator.mandatory.retain(|(k, _)| k != key),
+                    Relaxation::AllowOverlap(a, b) => {
+                        generator
+                            .collision_exceptions
+                            .insert((a.as_ref().clone(), b.as_ref().clone()));
+                    }
+                }
+                Suggestion {
+                    options: constraints.count_options(generator, limit, &filter),
+                    relaxation,
+                }
+            })
+            .filter(|suggestion| suggestion.options > 0)
+            .collect::<Vec<_>>();
+        suggestions.sort_by_key(|suggestion| Reverse(suggestion.options));
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{entry, SubjectBuilder};
+    use crate::models::{DaysOfTheWeek, Span};
+    use crate::option_generator::filters::OfSubject;
+    use crate::t;
+
+    #[test]
+    fn suggest_relaxations_test() {
+        let sa = Span::new(t!("08:00"), t!("10:00"));
+        let sb = Span::new(t!("09:00"), t!("11:00"));
+        let sc = Span::new(t!("11:00"), t!("13:00"));
+        let subjects = [
+            SubjectBuilder::new("01.01")
+                .credits(6)
+                .commissions(&[(DaysOfTheWeek::Monday, sa)])
+                .build(),
+            SubjectBuilder::new("01.02")
+                .credits(3)
+                .commissions(&[(DaysOfTheWeek::Monday, sb)])
+                .build(),
+            SubjectBuilder::new("01.03")
+                .credits(3)
+                .commissions(&[(DaysOfTheWeek::Monday, sc)])
+                .build(),
+        ];
+        let entry = |i: usize| entry(&subjects[i]);
+
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![entry(0), entry(1)])
+            .set_optional(vec![entry(2)]);
+        let constraints = Constraints {
+            credits: 9..=12,
+            subjects: 2..=3,
+        };
+        let suggestions = generator.suggest_relaxations(&constraints, 10);
+
+        assert_eq!(
+            suggestions
+                .iter()
+                .map(|s| (s.relaxation.to_string(), s.options))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "Allow 01.01 - 01.01 (6) ([\"0\"]) to overlap with 01.02 - 01.02 (3) ([\"0\"])"
+                        .to_owned(),
+                    2
+                ),
+                ("Drop subject 01.02".to_owned(), 1),
+            ]
+        );
+
+        let suggestions = generator.suggest_relaxations(
+            &Constraints {
+                credits: 0..=100,
+                subjects: 0..=100,
+            },
+            10,
+        );
+        assert_eq!(suggestions.len(), 3);
+
+        let without_second = |option: &Vec<Option<SubjectCommision>>| {
+            option
+                .iter()
+                .flatten()
+                .all(|c| c.subject_code() != t!("01.02"))
+        };
+        assert_eq!(
+            generator
+                .suggest_relaxations_with(&constraints, 10, without_second)
+                .iter()
+                .map(|s| (s.relaxation.to_string(), s.options))
+                .collect::<Vec<_>>(),
+            vec![("Drop subject 01.02".to_owned(), 1)]
+        );
+    }
+
+    #[test]
+    fn suggest_overlap_with_optional_test() {
+        let subjects = [
+            SubjectBuilder::new("01.01")
+                .commissions(&[(DaysOfTheWeek::Monday, Span::new(t!("08:00"), t!("10:00")))])
+                .build(),
+            SubjectBuilder::new("01.02")
+                .commissions(&[(DaysOfTheWeek::Monday, Span::new(t!("09:00"), t!("11:00")))])
+                .build(),
+        ];
+
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![entry(&subjects[0])])
+            .set_optional(vec![entry(&subjects[1])]);
+        let suggestions = generator.suggest_relaxations(
+            &Constraints {
+                credits: 0..=100,
+                subjects: 2..=2,
+            },
+            10,
+        );
+
+        assert!(suggestions
+            .iter()
+            .any(|s| matches!(s.relaxation, Relaxation::AllowOverlap(..)) && s.options == 1));
+    }
+}
@@ -0,0 +1,139 @@
+use super::{Chosen, OptionGenerator};
+use crate::models::Collidable;
+use core::hash::Hash;
+use rand::{RngExt, SeedableRng};
+use rand_pcg::Pcg64;
+use std::collections::HashSet;
+
+/// Random walks that may end in a dead end or repeat an earlier sample before giving up.
+const ATTEMPTS_PER_SAMPLE: usize = 1000;
+
+impl<K: Hash + Eq + Clone, T: Collidable + Hash + Eq + Clone> OptionGenerator<K, T> {
+    /// Draws up to `count` distinct options by walking the search tree at random, picking
+    /// uniformly among the choices that are still valid at each subject and starting over on
+    /// dead ends.
+    ///
+    /// This is fast on inputs too large to enumerate, but not uniform: an option is drawn with
+    /// probability proportional to the product of `1 / valid choices` along its path, so
+    /// options behind subjects with few valid commissions come up more often. Use
+    /// [`sample_exact`](Self::sample_exact) when every option must be equally likely.
+    pub fn sample(&self, count: usize, seed: u64) -> Vec<Vec<Option<T>>> {
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let context = self.search_context();
+        let groups = self.groups().collect::<Vec<_>>();
+
+        let mut seen = HashSet::new();
+        let mut samples = vec![];
+        let mut misses = 0;
+        for _ in 0..count.saturating_mul(ATTEMPTS_PER_SAMPLE) {
+            if samples.len() == count || misses == ATTEMPTS_PER_SAMPLE {
+                break;
+            }
+
+            let mut chosen: Chosen<K, T> = vec![];
+            for (position, (key, group)) in groups.iter().enumerate() {
+                let pending = &groups[position + 1..];
+                let candidates = group
+                    .items
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, val)| !context.collides(&chosen, key, val))
+                    .map(|(index, val)| (index, Some(val.clone())))
+                    .chain((!group.mandatory).then_some((group.items.len(), None)))
+                    .filter(|(index, val)| {
+                        let mut chosen = chosen.clone();
+                        chosen.push((key.clone(), *index, val.clone()));
+                        context.admits(&chosen, pending)
+                    })
+                    .collect::<Vec<_>>();
+                if candidates.is_empty() {
+                    break;
+                }
+                let (index, val) = candidates[rng.random_range(0..candidates.len())].clone();
+                chosen.push((key.clone(), index, val));
+            }
+
+            if chosen.len() == groups.len()
+                && seen.insert(
+                    chosen
+                        .iter()
+                        .map(|(_, index, _)| *index)
+                        .collect::<Vec<_>>(),
+                )
+            {
+                samples.push(chosen.into_iter().map(|(_, _, val)| val).collect());
+                misses = 0;
+            } else {
+                misses += 1;
+            }
+        }
+        samples
+    }
+
+    /// Draws up to `count` distinct options with every option equally likely. It has to
+    /// generate every option, so it is as slow as [`generate`](Self::generate).
+    pub fn sample_exact(self, count: usize, seed: u64) -> Vec<Vec<Option<T>>> {
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let mut samples = vec![];
+        for (i, option) in self.generate().enumerate() {
+            if i < count {
+                samples.push(option);
+            } else {
+                let j = rng.random_range(0..=i);
+                if j < count {
+                    samples[j] = option;
+                }
+            }
+        }
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Span;
+    use crate::t;
+    use itertools::Itertools;
+
+    fn generator() -> OptionGenerator<&'static str, Span> {
+        let sa = Span::new(t!("00:00"), t!("01:00"));
+        let sb = Span::new(t!("01:00"), t!("02:00"));
+        let sc = Span::new(t!("02:00"), t!("03:00"));
+        let mut generator = OptionGenerator::default();
+        generator
+            .set_mandatory(vec![("0", vec![sa, sc])])
+            .set_optional(vec![("1", vec![sa, sb, sc]), ("2", vec![sa, sb])]);
+        generator
+    }
+
+    #[test]
+    fn sample_test() {
+        let all = generator().generate().collect_vec();
+
+        let samples = generator().sample(5, 42);
+        assert_eq!(samples.len(), 5);
+        assert!(samples.iter().all(|sample| all.contains(sample)));
+        assert!(samples.iter().all_unique());
+        assert_eq!(samples, generator().sample(5, 42));
+
+        let samples = generator().sample(100, 42);
+        assert_eq!(samples.len(), all.len());
+        assert_eq!(generator().sample(usize::MAX, 1).len(), all.len());
+    }
+
+    #[test]
+    fn sample_exact_test() {
+        let all = generator().generate().collect_vec();
+
+        let samples = generator().sample_exact(5, 42);
+        assert_eq!(samples.len(), 5);
+        assert!(samples.iter().all(|sample| all.contains(sample)));
+        assert!(samples.iter().all_unique());
+        assert_eq!(samples, generator().sample_exact(5, 42));
+
+        let samples = generator().sample_exact(100, 42);
+        assert_eq!(samples, all);
+        assert_eq!(generator().sample_exact(usize::MAX, 1), all);
+    }
+}
@@ -0,0 +1,261 @@
+use super::filters::ChoiceFilter;
+use crate::models::{DaysOfTheWeek, SubjectCommision};
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+use std::cell::RefCell;
+use std::error::Error;
+use strum::IntoEnumIterator;
+
+/// What a script sees of an option: `subjects` (with `code`, `name`, `credits` and `names` of
+/// the commission), total `credits` and the `week`, a map from lowercase day names to their
+/// classes (with `subject`, `buildings`, and `start` and `end` in minutes after midnight).
+fn option_map(option: &[Option<SubjectCommision>]) -> Map {
+    let mut subjects = Array::new();
+    let mut credits = 0;
+    let mut week = DaysOfTheWeek::iter()
+        .map(|day| (format!("{day:?}").to_lowercase(), Array::new()))
+        .collect::<Vec<_>>();
+    for commission in option.iter().flatten() {
+        let subject = commission.subject.upgrade().unwrap();
+        let subject = subject.borrow();
+        credits += subject.credits as i64;
+        let mut map = Map::new();
+        map.insert("code".into(), subject.code.to_string().into());
+        map.insert("name".into(), subject.name.clone().into());
+        map.insert("credits".into(), (subject.credits as i64).into());
+        map.insert("names".into(), commission.names.clone().into());
+        subjects.push(map.into());
+
+        for (index, (_, day)) in commission.schedule.days.iter().enumerate() {
+            for task in &day.tasks {
+                let mut class = Map::new();
+                class.insert("subject".into(), subject.code.to_string().into());
+                class.insert(
+                    "start".into(),
+                    (task.span.start.minutes_since_midnight() as i64).into(),
+                );
+                class.insert(
+                    "end".into(),
+                    (task.span.end.minutes_since_midnight() as i64).into(),
+                );
+                class.insert(
+                    "buildings".into(),
+                    task.info
+                        .buildings
+                        .iter()
+                        .map(|building| building.name.clone())
+                        .collect::<Vec<_>>()
+                        .into(),
+                );
+                week[index].1.push(class.into());
+            }
+        }
+    }
+
+    let mut map = Map::new();
+    map.insert("subjects".into(), subjects.into());
+    map.insert("credits".into(), credits.into());
+    map.insert(
+        "week".into(),
+        week.into_iter()
+            .map(|(day, classes)| (day.into(), Dynamic::from_array(classes)))
+            .collect::<Map>()
+            .into(),
+    );
+    map
+}
+
+/// An option together with the score a script gave it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scored {
+    pub score: i64,
+    pub option: Vec<Option<SubjectCommision>>,
+}
+
+/// A Rhai script defining `fn accept(option)`, returning whether to keep the option, and/or
+/// `fn score(option)`, returning an integer where higher is better. Scripts run with limits on
+/// how much work a single call may do, so a runaway script fails instead of hanging.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    first_error: RefCell<Option<String>>,
+}
+
+impl Script {
+    pub fn compile(source: &str) -> Result<Self, Box<dyn Error>> {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(1_000_000)
+            .set_max_call_levels(32)
+            .set_max_string_size(10_000)
+            .set_max_array_size(10_000)
+            .set_max_map_size(10_000);
+        let ast = engine.compile(source)?;
+        let script = Self {
+            engine,
+            ast,
+            first_error: RefCell::new(None),
+        };
+        if !script.defines("accept") && !script.defines("score") {
+            return Err("The script defines neither accept(option) nor score(option).".into());
+        }
+        Ok(script)
+    }
+
+    /// Whether the script defines a function with this name taking an option.
+    pub fn defines(&self, function: &str) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == function && f.params.len() == 1)
+    }
+
+    /// The first error the script failed with while used as a filter, if any.
+    pub fn error(&self) -> Option<String> {
+        self.first_error.borrow().clone()
+    }
+
+    fn call<R: Clone + 'static>(
+        &self,
+        function: &str,
+        option: &[Option<SubjectCommision>],
+    ) -> Result<R, Box<dyn Error>> {
+        let option = Dynamic::from_map(option_map(option));
+        // Only the function runs, not the statements at the top level of the script.
+        Ok(self.engine.call_fn_with_options(
+            CallFnOptions::new().eval_ast(false),
+            &mut Scope::new(),
+            &self.ast,
+            function,
+            (option,),
+        )?)
+    }
+
+    pub fn accept(&self, option: &[Option<SubjectCommision>]) -> Result<bool, Box<dyn Error>> {
+        self.call("accept", option)
+    }
+
+    pub fn score(&self, option: &[Option<SubjectCommision>]) -> Result<i64, Box<dyn Error>> {
+        self.call("score", option)
+    }
+
+    /// Orders the options from highest to lowest score. Options with the same score keep their
+    /// generation order.
+    pub fn rank<I>(&self, options: I) -> Result<Vec<Scored>, Box<dyn Error>>
+    where
+        I: IntoIterator<Item = Vec<Option<SubjectCommision>>>,
+    {
+        let mut ranked = options
+            .into_iter()
+            .map(|option| {
+                Ok(Scored {
+                    score: self.score(&option)?,
+                    option,
+                })
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+        ranked.sort_by_key(|scored| std::cmp::Reverse(scored.score));
+        Ok(ranked)
+    }
+}
+
+/// Options the script fails on are rejected, and the first failure is kept for
+/// [`Script::error`]. A script without `accept` keeps every option.
+impl ChoiceFilter<SubjectCommision> for Script {
+    fn filter(&self, item: &Vec<Option<SubjectCommision>>) -> bool {
+        if !self.defines("accept") {
+            return true;
+        }
+        self.accept(item).unwrap_or_else(|error| {
+            self.first_error
+                .borrow_mut()
+                .get_or_insert_with(|| error.to_string());
+            false
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{class, entry, label, SubjectBuilder};
+    use crate::option_generator::filters::ChoiceIterator;
+    use crate::option_generator::OptionGenerator;
+
+    #[test]
+    fn script_filter_and_score() {
+        let subjects = [
+            SubjectBuilder::new("01.01")
+                .credits(6)
+                .commission("0", &[class(DaysOfTheWeek::Friday, "08:00", "10:00")])
+                .building("External")
+                .commission("1", &[class(DaysOfTheWeek::Monday, "14:00", "16:00")])
+                .building("External")
+                .build(),
+            SubjectBuilder::new("01.02")
+                .credits(4)
+                .commission("0", &[class(DaysOfTheWeek::Monday, "08:00", "10:00")])
+                .building("External")
+                .build(),
+        ];
+        let mut generator = OptionGenerator::default();
+        generator.set_optional(subjects.iter().map(entry).collect());
+        let script = Script::compile(
+            r#"
+            fn accept(option) {
+                for subject in option.subjects {
+                    if subject.code == "01.02" && subject.names[0] != "0" { return false; }
+                }
+                option.credits >= 6
+            }
+            fn score(option) {
+                let score = 0;
+                for class in option.week.monday {
+                    if "External" in class.buildings { score -= class.end - class.start; }
+                }
+                score - option.week.friday.len()
+            }
+            "#,
+        )
+        .unwrap();
+
+        let accepted = generator
+            .generate()
+            .filter_choices(|option: &Vec<_>| script.filter(option))
+            .collect::<Vec<_>>();
+        assert_eq!(accepted.len(), 4);
+        let ranked = script
+            .rank(accepted)
+            .unwrap()
+            .into_iter()
+            .map(|scored| (scored.score, label(&scored.option)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranked,
+            vec![
+                (-1, "0-".to_owned()),
+                (-120, "1-".to_owned()),
+                (-121, "00".to_owned()),
+                (-240, "10".to_owned()),
+            ]
+        );
+        assert_eq!(script.error(), None);
+        assert!(Script::compile("fn accept(option) {").is_err());
+        assert!(Script::compile("fn rank(option) { 0 }").is_err());
+
+        let failing = Script::compile("fn accept(option) { option.nope() }").unwrap();
+        assert!(failing.accept(&[]).is_err());
+        assert!(!failing.filter(&vec![]));
+        assert!(failing.error().unwrap().contains("nope"));
+
+        let scoring = Script::compile("fn score(option) { 0 }").unwrap();
+        assert!(!scoring.defines("accept"));
+        assert!(scoring.filter(&vec![]));
+        assert_eq!(scoring.error(), None);
+
+        let top_level = Script::compile("loop {} fn accept(option) { true }").unwrap();
+        assert!(top_level.filter(&vec![]));
+        assert_eq!(top_level.error(), None);
+
+        let endless = Script::compile("fn accept(option) { loop {} }").unwrap();
+        assert!(endless.accept(&[]).is_err());
+    }
+}
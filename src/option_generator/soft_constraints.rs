@@ -0,0 +1,110 @@
+use super::filters::ChoiceFilter;
+
+/// An option together with the sum of the weights of the soft constraints it breaks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Penalized<T> {
+    pub penalty: u32,
+    pub option: Vec<Option<T>>,
+}
+
+/// Preferences that, unlike filters, never discard an option. Every constraint the option does
+/// not pass adds its weight to the option's penalty.
+pub struct SoftConstraints<'a, T> {
+    constraints: Vec<(u32, Box<dyn ChoiceFilter<T> + 'a>)>,
+}
+
+impl<T> Default for SoftConstraints<'_, T> {
+    fn default() -> Self {
+        Self {
+            constraints: vec![],
+        }
+    }
+}
+
+impl<'a, T> SoftConstraints<'a, T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<F: ChoiceFilter<T> + 'a>(&mut self, weight: u32, constraint: F) -> &mut Self {
+        self.constraints.push((weight, Box::new(constraint)));
+        self
+    }
+
+    pub fn penalty(&self, option: &Vec<Option<T>>) -> u32 {
+        self.constraints
+            .iter()
+            .filter(|(_, constraint)| !constraint.filter(option))
+            .map(|(weight, _)| weight)
+            .sum()
+    }
+
+    /// Orders the options from least to most penalized. Options with the same penalty keep
+    /// their generation order. Hard constraints should already be applied to `options`.
+    pub fn rank<I>(&self, options: I) -> Vec<Penalized<T>>
+    where
+        I: IntoIterator<Item = Vec<Option<T>>>,
+    {
+        let mut ranked = options
+            .into_iter()
+            .map(|option| Penalized {
+                penalty: self.penalty(&option),
+                option,
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by_key(|penalized| penalized.penalty);
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{class, entry, label, SubjectBuilder};
+    use crate::models::DaysOfTheWeek;
+    use crate::option_generator::filters::{ChoiceIterator, FreeDays, NotBefore, SubjectCount};
+    use crate::option_generator::OptionGenerator;
+    use crate::t;
+    use itertools::Itertools;
+
+    #[test]
+    fn rank_by_penalty() {
+        let subjects = [
+            SubjectBuilder::new("01.01")
+                .commissions(&[
+                    class(DaysOfTheWeek::Friday, "09:00", "11:00"),
+                    class(DaysOfTheWeek::Monday, "07:00", "09:00"),
+                    class(DaysOfTheWeek::Tuesday, "10:00", "12:00"),
+                ])
+                .build(),
+            SubjectBuilder::new("01.02")
+                .commissions(&[class(DaysOfTheWeek::Friday, "14:00", "16:00")])
+                .build(),
+        ];
+        let mut generator = OptionGenerator::default();
+        generator.set_optional(subjects.iter().map(entry).collect());
+
+        let mut preferences = SoftConstraints::new();
+        preferences
+            .add(2, FreeDays::on([DaysOfTheWeek::Friday]))
+            .add(1, NotBefore(t!("08:00")));
+        let ranked = preferences
+            .rank(generator.generate().filter_choices(SubjectCount::new(1..)))
+            .into_iter()
+            .map(|penalized| (penalized.penalty, label(&penalized.option)))
+            .collect_vec();
+
+        assert_eq!(
+            ranked,
+            vec![
+                (0, "2-".to_owned()),
+                (1, "1-".to_owned()),
+                (2, "00".to_owned()),
+                (2, "0-".to_owned()),
+                (2, "20".to_owned()),
+                (2, "-0".to_owned()),
+                (3, "10".to_owned()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use crate::models::{DaysOfTheWeek, Span};
+use core::hash::Hash;
+use enum_map::EnumMap;
+use std::collections::HashMap;
+
+/// How many minutes two commissions may overlap and still be taken together. Every overlapping
+/// stretch is checked on its own against the most specific limit that applies to it: a
+/// per-subject limit (the stricter one if both subjects have it), else the limit for its day,
+/// else the global one.
+#[derive(Debug, Clone)]
+pub struct OverlapTolerance<K> {
+    pub minutes: u64,
+    pub per_subject: HashMap<K, u64>,
+    pub per_day: EnumMap<DaysOfTheWeek, Option<u64>>,
+}
+
+impl<K: Hash + Eq> OverlapTolerance<K> {
+    pub fn new(minutes: u64) -> Self {
+        Self {
+            minutes,
+            per_subject: HashMap::new(),
+            per_day: EnumMap::default(),
+        }
+    }
+
+    pub fn set_subject(&mut self, key: K, minutes: u64) -> &mut Self {
+        self.per_subject.insert(key, minutes);
+        self
+    }
+
+    pub fn set_day(&mut self, day: DaysOfTheWeek, minutes: u64) -> &mut Self {
+        self.per_day[day] = Some(minutes);
+        self
+    }
+
+    pub(super) fn allows(&self, key_a: &K, key_b: &K, day: DaysOfTheWeek, overlap: &Span) -> bool {
+        let limit = [key_a, key_b]
+            .into_iter()
+            .filter_map(|key| self.per_subject.get(key).copied())
+            .min()
+            .or(self.per_day[day])
+            .unwrap_or(self.minutes);
+        overlap.duration() <= limit
+    }
+}
+
+/// Two chosen commissions that overlap within the tolerance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToleratedOverlap<K, T> {
+    pub first: (K, T),
+    pub second: (K, T),
+    pub overlaps: Vec<(DaysOfTheWeek, Span)>,
+}